@@ -1,9 +1,126 @@
-use std::{env, path::PathBuf, sync::{LazyLock, RwLock}};
+use std::{env, fs, path::{Path, PathBuf}, process::{Command, Stdio}, sync::{LazyLock, RwLock}, time::{Duration, Instant}};
+use anyhow::{anyhow, Context};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use crate::cli::{Cli, LogLevel, StartPage};
+use crate::theme::{self, Theme, ThemeConfig};
+use crate::keymap::Keymap;
+use crate::i18n::{self, t, tf, Lang, Language, Msg};
+
+#[derive(Default, Clone, Serialize)]
+pub struct Backend {
+    #[serde(skip)]
+    pub name: String,
+    pub host: String,
+    pub key: String,
+}
+
+// 配置文件中除后端以外的设置，新的设置项加在这里
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub page: StartPage,
+    pub log_level: LogLevel,
+    pub theme: String,
+    // 自定义主题
+    pub themes: IndexMap<String, ThemeConfig>,
+    // 动作名称 -> 按键
+    pub keybindings: IndexMap<String, String>,
+    // 启用j/k、g/G、ctrl-d/ctrl-u
+    pub vim_keys: bool,
+    // 界面语言，auto、zh-CN或en
+    pub language: Language,
+    pub delay: DelaySettings,
+    pub log: LogSettings,
+    pub refresh: RefreshSettings,
+    pub table: TableSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            page: StartPage::default(),
+            log_level: LogLevel::default(),
+            theme: "auto".into(),
+            themes: IndexMap::new(),
+            keybindings: IndexMap::new(),
+            vim_keys: false,
+            language: Language::default(),
+            delay: DelaySettings::default(),
+            log: LogSettings::default(),
+            refresh: RefreshSettings::default(),
+            table: TableSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DelaySettings {
+    // 测速使用的地址
+    pub url: String,
+    // 超时，单位毫秒
+    pub timeout: u64,
+}
+
+impl Default for DelaySettings {
+    fn default() -> Self {
+        Self {
+            url: "https://www.gstatic.com/generate_204".into(),
+            timeout: 5000,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    // 日志界面最多缓存的行数
+    pub buffer_size: usize,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self { buffer_size: 5000 }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshSettings {
+    // 多后端总览的刷新间隔，单位毫秒
+    pub backend: u64,
+}
+
+impl Default for RefreshSettings {
+    fn default() -> Self {
+        Self { backend: 2000 }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TableSettings {
+    // 自动宽度的列的最大宽度，内容更长时截断并显示省略号，0为不限制
+    pub max_column_width: u16,
+}
 
 #[derive(Default, Clone)]
 pub struct Config {
     pub host: String,
     pub key: String,
+    // 启动时使用的后端名称，在命令行覆盖host和key之前按名称确定
+    pub backend: String,
+    // 配置文件中的所有后端，用于多后端总览
+    pub backends: Vec<Backend>,
+    pub settings: Settings,
+    pub theme: Theme,
+    pub keymap: Keymap,
+    pub lang: Lang,
+    // 实际使用的配置文件
+    pub path: Option<PathBuf>,
+    // 加载配置时发现的问题，比如未知的配置项
+    pub warnings: Vec<String>,
 }
 
 pub fn get_config() -> Config {
@@ -12,36 +129,286 @@ pub fn get_config() -> Config {
 
 pub static CONFIG: LazyLock<RwLock<Config>> = LazyLock::new(|| {
     RwLock::new(Config {
-        host: "127.0.0.1:9090".to_string(),
-        key: "".to_string(),
+        host: DEFAULT_HOST.to_string(),
+        ..Default::default()
     })
 });
 
-pub fn load_config() -> anyhow::Result<Config> {
-    let ini = "clash-tui.ini";
-    let default_host = "127.0.0.1".to_string();
-    let default_key = String::new();
+pub const DEFAULT_BACKEND: &str = "default";
+const DEFAULT_HOST: &str = "127.0.0.1:9090";
+const CONFIG_NAME: &str = "clash-tui";
+const CONFIG_EXTENSIONS: [&str; 4] = ["ini", "toml", "yaml", "yml"];
+// 后端分节中可以使用的配置项
+const BACKEND_KEYS: [&str; 4] = ["host", "key", "key_file", "key_command"];
+// key_command的最长执行时间
+const KEY_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
 
-    let mut path = env::current_exe()?.parent().unwrap_or(PathBuf::from("").as_path()).join(ini);
-    if !path.exists() {
-        path = env::current_dir()?.join(ini);
+// 按顺序查找配置文件的目录
+fn config_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![];
+    if let Some(dir) = env::current_exe().ok().as_ref().and_then(|x| x.parent()) {
+        dirs.push(dir.to_path_buf());
+    }
+    if let Ok(dir) = env::current_dir() {
+        dirs.push(dir);
     }
+    if let Some(dir) = env::var_os("XDG_CONFIG_HOME").filter(|x| !x.is_empty()) {
+        dirs.push(PathBuf::from(dir).join(CONFIG_NAME));
+    }
+    if let Some(dir) = env::var_os("HOME").filter(|x| !x.is_empty()) {
+        dirs.push(PathBuf::from(dir).join(".config").join(CONFIG_NAME));
+    }
+    dirs
+}
+
+// 保存列设置等界面状态的目录，有配置文件时为它所在的目录，否则为XDG_CONFIG_HOME或~/.config下的clash-tui
+pub fn config_dir(config: &Config) -> Option<PathBuf> {
+    if let Some(dir) = config.path.as_ref().and_then(|x| x.parent()) {
+        return Some(dir.to_path_buf());
+    }
+    match env::var_os("XDG_CONFIG_HOME").filter(|x| !x.is_empty()) {
+        Some(dir) => Some(PathBuf::from(dir).join(CONFIG_NAME)),
+        None => env::var_os("HOME")
+            .filter(|x| !x.is_empty())
+            .map(|dir| PathBuf::from(dir).join(".config").join(CONFIG_NAME)),
+    }
+}
 
-    if !path.exists() {
-        return Ok(Config { host: default_host, key: default_key })
+fn find_config_file(cli: &Cli) -> anyhow::Result<Option<PathBuf>> {
+    if let Some(path) = &cli.config {
+        if !path.exists() {
+            return Err(anyhow!(tf(Msg::ConfigNotFound, &[&path.display()])));
+        }
+        return Ok(Some(path.clone()));
     }
-    let settings = config::Config::builder()
-        .add_source(config::File::with_name(path.to_str().unwrap_or(ini)))
-        .build()?;
 
-    let args: Vec<String> = env::args().collect();
-    let key = if args.len() > 1 {
-        (format!("{}.host", &args[1]), format!("{}.key", &args[1]))
+    Ok(config_dirs()
+        .into_iter()
+        .flat_map(|dir| CONFIG_EXTENSIONS.map(|ext| dir.join(format!("{CONFIG_NAME}.{ext}"))))
+        .find(|path| path.exists()))
+}
+
+// 读取一个后端，key依次从key、key_file、key_command中获取
+fn read_backend(name: &str, section: &config::Map<String, config::Value>, dir: &Path, warnings: &mut Vec<String>) -> anyhow::Result<Backend> {
+    let get = |k: &str| section.get(k).map(ToString::to_string).filter(|x| !x.is_empty());
+    let key = if let Some(key) = get("key") {
+        key
+    } else if let Some(file) = get("key_file") {
+        let file = match file.strip_prefix("~/") {
+            Some(rest) => PathBuf::from(env::var_os("HOME").unwrap_or_default()).join(rest),
+            None => dir.join(file),
+        };
+        if let Some(warning) = check_key_file_permissions(&file) {
+            warnings.push(tf(Msg::BackendWarning, &[&name, &warning]));
+        }
+        fs::read_to_string(&file)
+            .with_context(|| tf(Msg::ReadKeyFileError, &[&name, &file.display()]))?
+            .trim()
+            .to_string()
+    } else if let Some(command) = get("key_command") {
+        run_key_command(&command).with_context(|| tf(Msg::KeyCommandError, &[&name, &command]))?
     } else {
-        ("host".into(), "key".into())
+        String::new()
     };
+    Ok(Backend {
+        name: name.to_string(),
+        host: get("host").unwrap_or(DEFAULT_HOST.into()),
+        key,
+    })
+}
+
+#[cfg(unix)]
+fn check_key_file_permissions(file: &Path) -> Option<String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = fs::metadata(file).ok()?.permissions().mode();
+    if mode & 0o077 != 0 {
+        Some(tf(Msg::KeyFilePermissive, &[&file.display(), &format!("{:o}", mode & 0o777)]))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn check_key_file_permissions(_file: &Path) -> Option<String> {
+    None
+}
+
+fn run_key_command(command: &str) -> anyhow::Result<String> {
+    let (shell, arg) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .args([arg, command])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // 命令卡住(比如等待输入密码)时不能一直等下去，超时后结束它
+    let start = Instant::now();
+    while child.try_wait()?.is_none() {
+        if start.elapsed() > KEY_COMMAND_TIMEOUT {
+            child.kill().unwrap_or(());
+            child.wait()?;
+            return Err(anyhow!(tf(Msg::KeyCommandTimeout, &[&KEY_COMMAND_TIMEOUT.as_secs()])));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow!("{} {}", output.status, stderr.trim()));
+    }
+    // 与pass等工具的习惯一致，只取第一行
+    let stdout = String::from_utf8(output.stdout)?;
+    Ok(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+// 合并顺序：命令行(含环境变量) > 配置文件 > 默认值
+pub fn load_config(cli: &Cli) -> anyhow::Result<Config> {
+    let mut backends = vec![];
+    let mut settings = Settings::default();
+    let mut warnings = vec![];
+
+    let path = find_config_file(cli)?;
+    if let Some(path) = &path {
+        let file = config::Config::builder()
+            .add_source(config::File::from(path.as_path()))
+            .build()?;
+
+        // 没有分节的host/key为默认后端，每个含有host的分节为一个命名后端，其余的为设置
+        let table: IndexMap<String, config::Value> = file.try_deserialize()?;
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut unknown = vec![];
+        let mut rest = config::Map::new();
+        if BACKEND_KEYS.iter().any(|x| table.contains_key(*x)) {
+            let section = table.iter()
+                .filter(|(k, _)| BACKEND_KEYS.contains(&k.as_str()))
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            backends.push(read_backend(DEFAULT_BACKEND, &section, &dir, &mut warnings)?);
+        }
+        for (name, value) in table {
+            if BACKEND_KEYS.contains(&name.as_str()) {
+                continue;
+            }
+            match value.clone().into_table() {
+                Ok(section) if section.contains_key("host") => {
+                    for key in section.keys().filter(|x| !BACKEND_KEYS.contains(&x.as_str())) {
+                        unknown.push(format!("{name}.{key}"));
+                    }
+                    backends.push(read_backend(&name, &section, &dir, &mut warnings)?);
+                }
+                _ => {
+                    rest.insert(name, value);
+                }
+            }
+        }
+        settings = serde_ignored::deserialize(config::Value::new(None, rest), |path| unknown.push(path.to_string()))
+            .map_err(|e| anyhow!("{}: {e}", path.display()))?;
+        warnings.extend(unknown.into_iter().map(|x| tf(Msg::UnknownConfig, &[&x])));
+    }
+    if backends.is_empty() {
+        backends.push(Backend { name: DEFAULT_BACKEND.into(), host: DEFAULT_HOST.into(), key: String::new() });
+    }
 
-    let host: String = settings.get(&key.0).unwrap_or(default_host);
-    let key: String = settings.get(&key.1).unwrap_or(default_key);
-    Ok(Config { host, key })
+    let mut backend = match cli.profile() {
+        Some(profile) => backends.iter()
+            .find(|x| x.name == profile)
+            .ok_or_else(|| anyhow!(tf(Msg::ProfileNotFound, &[&profile])))?,
+        None => backends.first().unwrap(),
+    }.clone();
+    if let Some(host) = &cli.host {
+        backend.host = host.clone();
+    }
+    if let Some(secret) = &cli.secret {
+        backend.key = secret.clone();
+    }
+    // 命令行覆盖的地址和key也用于后端列表，多后端总览和切换后端时与实际连接的一致
+    if let Some(x) = backends.iter_mut().find(|x| x.name == backend.name) {
+        *x = backend.clone();
+    }
+    if let Some(page) = cli.page {
+        settings.page = page;
+    }
+    if let Some(log_level) = cli.log_level {
+        settings.log_level = log_level;
+    }
+    let theme = theme::resolve(&settings.theme, &settings.themes, &mut warnings);
+    let keymap = Keymap::load(&settings.keybindings, settings.vim_keys, &mut warnings);
+    let lang = i18n::resolve(settings.language);
+
+    Ok(Config {
+        host: backend.host,
+        key: backend.key,
+        backend: backend.name,
+        backends,
+        settings,
+        theme,
+        keymap,
+        lang,
+        path,
+        warnings,
+    })
+}
+
+// 每秒检查一次配置文件的修改时间，修改后重新加载，结果交给f处理
+pub fn watch_config<F>(cli: Cli, path: PathBuf, f: F)
+    where
+        F: Fn(anyhow::Result<Config>) + Send + 'static {
+    tokio::spawn(async move {
+        let modified = |path: PathBuf| async move { tokio::fs::metadata(path).await.and_then(|x| x.modified()).ok() };
+        let mut last = modified(path.clone()).await;
+        loop {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let current = modified(path.clone()).await;
+            if current != last {
+                last = current;
+                // 文件被删除或正在被替换时不处理，等待下一次修改
+                if current.is_some() {
+                    // 读取文件和执行key_command会阻塞，不能在异步任务中直接执行
+                    let cli = cli.clone();
+                    match tokio::task::spawn_blocking(move || load_config(&cli)).await {
+                        Ok(config) => f(config),
+                        Err(e) => f(Err(e.into())),
+                    }
+                }
+            }
+        }
+    });
+}
+
+// 隐藏secret，只显示是否设置
+pub fn mask(secret: &str) -> String {
+    if secret.is_empty() { String::new() } else { "******".into() }
+}
+
+// 以toml格式输出合并后的配置，secret会被隐藏
+pub fn print_config(config: &Config) -> anyhow::Result<String> {
+    #[derive(Serialize)]
+    struct Effective<'a> {
+        host: &'a str,
+        key: &'a str,
+        #[serde(flatten)]
+        settings: &'a Settings,
+        #[serde(flatten)]
+        backends: IndexMap<&'a str, &'a Backend>,
+    }
+
+    let backends = config.backends.iter()
+        .filter(|x| x.name != DEFAULT_BACKEND)
+        .map(|x| Backend { key: mask(&x.key), ..x.clone() })
+        .collect::<Vec<_>>();
+    let key = mask(&config.key);
+    let effective = Effective {
+        host: &config.host,
+        key: &key,
+        settings: &config.settings,
+        backends: backends.iter()
+            .map(|x| (x.name.as_str(), x))
+            .collect(),
+    };
+    let mut result = match &config.path {
+        Some(path) => format!("{}\n", tf(Msg::PrintConfigPath, &[&path.display()])),
+        None => format!("{}\n", t(Msg::PrintConfigDefault)),
+    };
+    result.push_str(&toml::to_string(&effective)?);
+    Ok(result)
 }
@@ -4,112 +4,162 @@ mod connection;
 
 pub use log::LogItem;
 use std::collections::HashMap;
-use reqwest::Client;
+use reqwest::{Client, Method, RequestBuilder, Url};
 use serde::de::DeserializeOwned;
 use serde_json::{json, Value};
 use tokio::join;
-use crate::app_config::get_config;
 pub use connection::{Connection, ConnectionItem};
 pub use proxy::{Provider, ProviderItem, Proxy, ProxyData, ProxyItem};
 use anyhow::{anyhow, Result};
+use crate::i18n::{t, Msg};
 
-async fn http_get<U: AsRef<str>, T: DeserializeOwned>(uri: U, params: &[(U, U)]) -> anyhow::Result<T> {
-    let config = get_config();
-    let mut url = format!("http://{}{}", config.host, uri.as_ref());
-    if !params.is_empty() {
-        url = reqwest::Url::parse_with_params(&url, params)?.to_string();
+// 一个clash后端的访问句柄，每个后端各持有一个
+#[derive(Clone)]
+pub struct ClashApi {
+    host: String,
+    key: String,
+    client: Client,
+}
+
+impl ClashApi {
+    pub fn new(host: &str, key: &str) -> Self {
+        Self {
+            host: host.to_string(),
+            key: key.to_string(),
+            client: Client::default(),
+        }
     }
-    let mut proxies = Client::default().get(url);
-    if !config.key.is_empty() {
-        proxies = proxies.header(
-            reqwest::header::AUTHORIZATION,
-            format!("Bearer {}", config.key),
-        )
+
+    // 去掉消息中的secret，websocket的地址中会带有token参数
+    pub fn redact(&self, msg: &str) -> String {
+        redact(msg, &self.key)
     }
 
-    let value = proxies
-        .send()
-        .await?
-        .json::<T>()
-        .await?;
-    Ok(value)
-}
+    // host由用户填写，格式不对时返回错误
+    pub fn ws_url(&self, uri: &str, params: &[(&str, &str)]) -> Result<Url> {
+        let url = format!("ws://{}{uri}", self.host);
+        let mut params = params.to_vec();
+        params.push(("token", &self.key));
+        Url::parse_with_params(&url, &params).map_err(|e| anyhow!("{url}: {e}"))
+    }
 
-pub async fn load_proxy() -> anyhow::Result<ProxyData> {
-    async fn get_proxies() ->anyhow::Result<HashMap<String, ProxyItem>> {
-        Ok(http_get::<&str, Proxy>("/proxies", &[]).await?.proxies)
+    fn request(&self, method: Method, uri: &str) -> RequestBuilder {
+        let req = self.client.request(method, format!("http://{}{uri}", self.host));
+        if self.key.is_empty() {
+            req
+        } else {
+            req.header(
+                reqwest::header::AUTHORIZATION,
+                format!("Bearer {}", self.key),
+            )
+        }
     }
-    async fn get_providers() -> anyhow::Result<HashMap<String, ProviderItem>> {
-        Ok(http_get::<&str, Provider>("/providers/proxies", &[]).await?.providers)
+
+    async fn http_get<T: DeserializeOwned>(&self, uri: &str, params: &[(&str, &str)]) -> Result<T> {
+        let value = self.request(Method::GET, uri)
+            .query(params)
+            .send()
+            .await?
+            .json::<T>()
+            .await?;
+        Ok(value)
     }
 
-    let result = join!(
-        get_proxies(),
-        get_providers()
-    );
+    pub async fn load_proxy(&self) -> Result<ProxyData> {
+        let get_proxies = async {
+            Ok::<HashMap<String, ProxyItem>, anyhow::Error>(self.http_get::<Proxy>("/proxies", &[]).await?.proxies)
+        };
+        let get_providers = async {
+            Ok::<HashMap<String, ProviderItem>, anyhow::Error>(self.http_get::<Provider>("/providers/proxies", &[]).await?.providers)
+        };
 
-    Ok(ProxyData {
-        proxies: result.0?,
-        providers: result.1?,
-    })
-}
+        let result = join!(
+            get_proxies,
+            get_providers
+        );
 
-pub async fn check_delay(group: &str) -> anyhow::Result<()> {
-    let params = [
-        ("url", "https://www.gstatic.com/generate_204"),
-        ("timeout", "5000"),
-    ];
-    let _: Value = http_get(format!("/group/{group}/delay").as_str(), &params).await?;
-    Ok(())
-}
+        Ok(ProxyData {
+            proxies: result.0?,
+            providers: result.1?,
+        })
+    }
 
-pub async fn select_group_current(group: &str, current: &str) -> Result<()> {
-    let config = get_config();
-    let url = format!("http://{}/proxies/{group}", config.host);
-    let resp = Client::default()
-        .put(url)
-        .json(&json!({"name":current}))
-        .header(
-            reqwest::header::AUTHORIZATION,
-            format!("Bearer {}", config.key),
-        )
-        .send()
-        .await?;
-    resp.text().await?;
-    Ok(())
-}
+    pub async fn load_connections(&self) -> Result<Connection> {
+        self.http_get("/connections", &[]).await
+    }
+
+    pub async fn close_connection(&self, id: &str) -> Result<()> {
+        self.request(Method::DELETE, &format!("/connections/{id}"))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
 
-pub async fn get_mode() -> Result<String> {
-    let config = get_config();
-    let url = format!("http://{}/configs", config.host);
-    let resp = Client::default()
-        .get(url)
-        .header(
-            reqwest::header::AUTHORIZATION,
-            format!("Bearer {}", config.key),
-        )
-        .send()
-        .await?;
-    let j: Value = resp.json().await?;
-    if j.get("message").and_then(|x|x.as_str()).unwrap_or("") == "Unauthorized" {
-        return Err(anyhow!("认证失败：请确认key是否正确"));
+    pub async fn close_all_connections(&self) -> Result<()> {
+        self.request(Method::DELETE, "/connections")
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn check_delay(&self, group: &str, url: &str, timeout: u64) -> Result<()> {
+        let timeout = timeout.to_string();
+        let params = [
+            ("url", url),
+            ("timeout", &timeout),
+        ];
+        // 名称中可能有#、?、/等字符，作为路径的一段需要编码
+        let _: Value = self.http_get(&format!("/group/{}/delay", urlencoding::encode(group)), &params).await?;
+        Ok(())
+    }
+
+    pub async fn check_proxy_delay(&self, proxy: &str, url: &str, timeout: u64) -> Result<()> {
+        let timeout = timeout.to_string();
+        let params = [
+            ("url", url),
+            ("timeout", &timeout),
+        ];
+        let _: Value = self.http_get(&format!("/proxies/{}/delay", urlencoding::encode(proxy)), &params).await?;
+        Ok(())
+    }
+
+    pub async fn select_group_current(&self, group: &str, current: &str) -> Result<()> {
+        let resp = self.request(Method::PUT, &format!("/proxies/{}", urlencoding::encode(group)))
+            .json(&json!({"name":current}))
+            .send()
+            .await?;
+        resp.text().await?;
+        Ok(())
+    }
+
+    pub async fn get_mode(&self) -> Result<String> {
+        let resp = self.request(Method::GET, "/configs")
+            .send()
+            .await?;
+        let j: Value = resp.json().await?;
+        if j.get("message").and_then(|x|x.as_str()).unwrap_or("") == "Unauthorized" {
+            return Err(anyhow!(t(Msg::AuthFailed)));
+        }
+        let mode = j.get("mode").ok_or(anyhow!("mode not found"))?.as_str().ok_or(anyhow!("mode not found"))?;
+        Ok(mode.to_string())
+    }
+
+    pub async fn set_mode(&self, mode: &str) -> Result<()> {
+        let resp = self.request(Method::PATCH, "/configs")
+            .json(&json!({"mode":mode}))
+            .send()
+            .await?;
+        resp.text().await?;
+        Ok(())
     }
-    let mode = j.get("mode").ok_or(anyhow!("mode not found"))?.as_str().ok_or(anyhow!("mode not found"))?;
-    Ok(mode.to_string())
 }
 
-pub async fn set_mode(mode: &str) -> Result<()> {
-    let config = get_config();
-    let url = format!("http://{}/configs", config.host);
-    let resp = Client::default()
-        .patch(url)
-        .json(&json!({"mode":mode}))
-        .header(
-            reqwest::header::AUTHORIZATION,
-            format!("Bearer {}", config.key),
-        )
-        .send()
-        .await?;
-    resp.text().await?;
-    Ok(())
-}
\ No newline at end of file
+pub fn redact(msg: &str, secret: &str) -> String {
+    if secret.is_empty() {
+        return msg.to_string();
+    }
+    let encoded: String = url::form_urlencoded::byte_serialize(secret.as_bytes()).collect();
+    msg.replace(secret, "******").replace(&encoded, "******")
+}
@@ -9,7 +9,6 @@ pub struct ProxyData {
 
 impl ProxyData {
     // 可切换代理
-    #[allow(dead_code)]
     pub fn get_groups(&self) -> Vec<&str> {
         let global = self.proxies.get("GLOBAL");
         match global {
@@ -21,7 +20,7 @@ impl ProxyData {
                     .unwrap()
                     .all
                     .iter()
-                    .filter(|it| self.proxies.get(*it).map_or(false, |x| !x.all.is_empty()))
+                    .filter(|it| self.proxies.get(*it).is_some_and(|x| !x.all.is_empty()))
                     .map(String::as_str)
                     .collect::<Vec<&str>>();
                 groups.push("GLOBAL");
@@ -64,17 +63,12 @@ pub struct ProviderProxy {
 }
 
 impl ProxyItem {
-    pub fn get_delay(&self, proxies: &HashMap<String, ProxyItem>) -> String {
+    // 最近一次测速的延迟，None为没有测过，0为超时；分组使用当前节点的延迟
+    pub fn get_delay(&self, proxies: &HashMap<String, ProxyItem>) -> Option<i32> {
         if self.now.is_empty() {
-            self.history.last().map_or("".to_string(), |x| {
-                if x.delay == 0 {
-                    "-".to_string()
-                } else {
-                    format!("{}ms", x.delay)
-                }
-            })
+            self.history.last().map(|x| x.delay)
         } else {
-            proxies.get(&self.now).unwrap().get_delay(proxies)
+            proxies.get(&self.now).and_then(|x| x.get_delay(proxies))
         }
     }
 }
@@ -101,36 +95,3 @@ pub struct ProxyItem {
     #[serde(default = "Vec::new")]
     pub history: Vec<HistoryItem>,
 }
-
-impl ProxyData {
-    pub fn to_group_items(&self, name: &str) -> Vec<Vec<String>> {
-        let proxy = self.proxies.get(name).unwrap();
-        let now = &proxy.now;
-
-        self.proxies
-            .get(name)
-            .unwrap()
-            .all
-            .iter()
-            .map(|v| {
-                vec![
-                    v.clone(),
-                    self.proxies.get(v).unwrap().get_delay(&self.proxies),
-                    if now == v { "✓" } else { "" }.to_string(),
-                ]
-            })
-            .collect()
-    }
-
-    pub fn to_groups(&self) -> Vec<Vec<String>> {
-        self.get_groups()
-            .into_iter()
-            .map(|x| {
-                let proxy = self.proxies.get(x).unwrap();
-                let now = proxy.now.clone();
-                let delay = self.proxies.get(&now).unwrap().get_delay(&self.proxies);
-                vec![proxy.name.clone(), now, delay]
-            })
-            .collect()
-    }
-}
@@ -4,21 +4,7 @@ use base64::{prelude::BASE64_STANDARD_NO_PAD, Engine};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use subs_url::SubsUrl;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppConfig {
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub subs: Vec<Subscription>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    pub connection: Vec<Connection>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct Subscription {
-    pub id: String,
-    pub name: String,
-    pub url: String,
-}
+use crate::i18n::{t, tf, Msg};
 
 fn is_zero(value: &u16) -> bool {
     *value == 0
@@ -56,11 +42,10 @@ pub struct Tls {
 pub async fn subscribe<T: AsRef<str>>(url: T) -> anyhow::Result<Vec<Connection>> {
     let resp = Client::default().get(url.as_ref()).send().await?;
     if resp.status().as_u16() >= 300 {
-        return Err(anyhow::anyhow!(
-            "http请求错误: code={}, resp={}",
-            resp.status(),
-            resp.text().await.unwrap_or("未知错误".to_string())
-        ));
+        return Err(anyhow::anyhow!(tf(
+            Msg::HttpError,
+            &[&resp.status(), &resp.text().await.unwrap_or(t(Msg::UnknownError).to_string())]
+        )));
     }
     decode(resp.text().await?)
 }
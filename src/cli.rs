@@ -0,0 +1,117 @@
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use crate::i18n::{t, Msg};
+use crate::page::{BackendPage, ConnectionPage, GroupPage, LogPage};
+
+// 命令行参数，优先级为：命令行 > 环境变量 > 配置文件 > 默认值
+#[derive(Parser, Debug, Clone, Default)]
+#[command(version)]
+pub struct Cli {
+    #[arg(long, env = "CLASH_TUI_HOST")]
+    pub host: Option<String>,
+
+    #[arg(long, env = "CLASH_TUI_SECRET", hide_env_values = true)]
+    pub secret: Option<String>,
+
+    #[arg(long, value_name = "PATH", env = "CLASH_TUI_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    #[arg(long, value_name = "NAME", env = "CLASH_TUI_PROFILE")]
+    pub profile: Option<String>,
+
+    #[arg(long, value_enum, env = "CLASH_TUI_PAGE")]
+    pub page: Option<StartPage>,
+
+    #[arg(long, value_enum, env = "CLASH_TUI_LOG_LEVEL")]
+    pub log_level: Option<LogLevel>,
+
+    #[arg(long)]
+    pub print_config: bool,
+
+    #[arg(value_name = "PROFILE", conflicts_with = "profile", hide = true)]
+    pub legacy_profile: Option<String>,
+}
+
+impl Cli {
+    // 帮助文字跟随界面的语言，此时还没有读取配置文件，按环境变量选择
+    pub fn parse_localized() -> Self {
+        let command = Self::command()
+            .about(t(Msg::CliAbout))
+            .mut_arg("host", |x| x.help(t(Msg::CliHost)))
+            .mut_arg("secret", |x| x.help(t(Msg::CliSecret)))
+            .mut_arg("config", |x| x.help(t(Msg::CliConfig)))
+            .mut_arg("profile", |x| x.help(t(Msg::CliProfile)))
+            .mut_arg("page", |x| x.help(t(Msg::CliPage)))
+            .mut_arg("log_level", |x| x.help(t(Msg::CliLogLevel)))
+            .mut_arg("print_config", |x| x.help(t(Msg::CliPrintConfig)))
+            .mut_arg("legacy_profile", |x| x.help(t(Msg::CliLegacyProfile)));
+        Self::from_arg_matches(&command.get_matches()).unwrap_or_else(|e| e.exit())
+    }
+
+    pub fn profile(&self) -> Option<&str> {
+        self.profile.as_deref().or(self.legacy_profile.as_deref())
+    }
+}
+
+// 界面名称，--page、配置文件的page和命令面板的page命令共用；别名为以前使用的名称
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StartPage {
+    #[default]
+    #[value(alias = "group")]
+    #[serde(alias = "group")]
+    Proxy,
+    #[value(alias = "logs")]
+    #[serde(alias = "logs")]
+    Log,
+    #[value(alias = "connections")]
+    #[serde(alias = "connections")]
+    Connection,
+    #[value(alias = "backends")]
+    #[serde(alias = "backends")]
+    Backend,
+}
+
+impl StartPage {
+    pub fn id(self) -> &'static str {
+        match self {
+            StartPage::Proxy => GroupPage::ID,
+            StartPage::Log => LogPage::ID,
+            StartPage::Connection => ConnectionPage::ID,
+            StartPage::Backend => BackendPage::ID,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StartPage::Proxy => "proxy",
+            StartPage::Log => "log",
+            StartPage::Connection => "connection",
+            StartPage::Backend => "backend",
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Debug,
+    #[default]
+    Info,
+    Warning,
+    Error,
+    Silent,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warning => "warning",
+            LogLevel::Error => "error",
+            LogLevel::Silent => "silent",
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::env;
+use std::io::{self, Write};
+use base64::{prelude::BASE64_STANDARD, Engine};
+
+// 通过终端的OSC 52转义序列复制到剪贴板，ssh远程使用时也能复制到本地；终端不支持时没有效果，也无法知道是否成功
+// out为终端的后端，在两次绘制之间写入，避免插到一帧的输出中间
+pub fn copy<W: Write>(out: &mut W, text: &str) -> io::Result<()> {
+    out.write_all(sequence(text).as_bytes())?;
+    out.flush()
+}
+
+// tmux和screen不会转发OSC 52，需要包在DCS中交给外层的终端
+fn sequence(text: &str) -> String {
+    let osc = format!("\x1b]52;c;{}\x07", BASE64_STANDARD.encode(text));
+    if env::var_os("TMUX").is_some() {
+        // tmux要求其中的ESC写两次
+        format!("\x1bPtmux;{}\x1b\\", osc.replace('\x1b', "\x1b\x1b"))
+    } else if env::var("TERM").is_ok_and(|x| x.starts_with("screen")) {
+        format!("\x1bP{osc}\x1b\\")
+    } else {
+        osc
+    }
+}
@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+use anyhow::Context;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use crate::i18n::{tf, Msg};
+
+// 保存列设置的文件，放在配置目录中
+const COLUMNS_FILE: &str = "columns.toml";
+
+// 表格中一列的显示设置，保存时按显示顺序排列
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub hidden: bool,
+    // 固定宽度，None时根据内容自动调整
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u16>,
+    // 固定的列显示在最前面，宽度不会被压缩
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+}
+
+impl ColumnConfig {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), hidden: false, width: None, pinned: false }
+    }
+}
+
+fn is_false(x: &bool) -> bool {
+    !x
+}
+
+#[derive(Default)]
+struct Columns {
+    // 读取和保存的文件，没有配置目录时为None，此时不保存
+    path: Option<PathBuf>,
+    // 界面id -> 列设置
+    pages: IndexMap<String, Vec<ColumnConfig>>,
+}
+
+static COLUMNS: LazyLock<RwLock<Columns>> = LazyLock::new(|| RwLock::new(Columns::default()));
+
+// 启动时读取配置目录中保存的列设置，文件不存在时使用默认设置，出错时加入warnings
+pub fn load(dir: Option<&Path>, warnings: &mut Vec<String>) {
+    let path = dir.map(|x| x.join(COLUMNS_FILE));
+    let pages = match &path {
+        Some(path) if path.exists() => match read(path) {
+            Ok(pages) => pages,
+            Err(e) => {
+                warnings.push(tf(Msg::LoadColumnsError, &[&format!("{e:#}")]));
+                IndexMap::new()
+            }
+        },
+        _ => IndexMap::new(),
+    };
+    *COLUMNS.write().unwrap() = Columns { path, pages };
+}
+
+fn read(path: &Path) -> anyhow::Result<IndexMap<String, Vec<ColumnConfig>>> {
+    let text = fs::read_to_string(path).with_context(|| path.display().to_string())?;
+    toml::from_str(&text).with_context(|| path.display().to_string())
+}
+
+// 界面保存的列设置
+pub fn get(page: &str) -> Option<Vec<ColumnConfig>> {
+    COLUMNS.read().unwrap().pages.get(page).cloned()
+}
+
+// 保存界面的列设置，None表示恢复默认设置
+pub fn save(page: &str, columns: Option<Vec<ColumnConfig>>) -> anyhow::Result<()> {
+    let mut all = COLUMNS.write().unwrap();
+    match columns {
+        Some(columns) => all.pages.insert(page.to_string(), columns),
+        None => all.pages.shift_remove(page),
+    };
+    let Some(path) = &all.path else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| dir.display().to_string())?;
+    }
+    fs::write(path, toml::to_string(&all.pages)?).with_context(|| path.display().to_string())
+}
@@ -0,0 +1,98 @@
+use clap::ValueEnum;
+use crate::clash_api::ProxyData;
+use crate::cli::StartPage;
+use crate::my_event::AppEvent;
+use crate::i18n::{t, tf, Msg};
+
+const MODES: [&str; 3] = ["rule", "global", "direct"];
+
+// 命令补全和解析时需要的数据
+pub struct CommandContext<'a> {
+    pub proxy: Option<&'a ProxyData>,
+    pub backends: Vec<String>,
+}
+
+impl CommandContext<'_> {
+    // 可以切换节点的分组，名称长的在前，解析时优先匹配
+    fn groups(&self) -> Vec<&str> {
+        let mut groups = self.proxy.map(ProxyData::get_groups).unwrap_or_default();
+        groups.sort_by_key(|x| std::cmp::Reverse(x.len()));
+        groups
+    }
+
+    fn group_members(&self, group: &str) -> &[String] {
+        self.proxy
+            .and_then(|x| x.proxies.get(group))
+            .map(|x| x.all.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+// 所有可以补全的命令
+pub fn candidates(ctx: &CommandContext) -> Vec<String> {
+    let mut result = vec![];
+    result.extend(StartPage::value_variants().iter().map(|x| format!("page {}", x.name())));
+    result.extend(MODES.iter().map(|x| format!("mode {x}")));
+    for group in ctx.groups() {
+        result.push(format!("test {group}"));
+        result.extend(ctx.group_members(group).iter().map(|x| format!("select {group} {x}")));
+    }
+    result.extend(ctx.backends.iter().map(|x| format!("profile {x}")));
+    result.push("close-conn all".into());
+    result.push("quit".into());
+    result
+}
+
+// 把命令解析为与按键相同的AppEvent
+pub fn parse(line: &str, ctx: &CommandContext) -> Result<AppEvent, String> {
+    let line = line.trim();
+    let (name, args) = line.split_once(' ').unwrap_or((line, ""));
+    let args = args.trim();
+    match name {
+        // 与--page相同的名称，分组详情界面需要参数，不能直接切换
+        "page" => match StartPage::from_str(args, false) {
+            Ok(page) => Ok(AppEvent::ShowPage(page.id(), None)),
+            Err(_) => {
+                let pages = StartPage::value_variants().iter().map(|x| x.name()).collect::<Vec<_>>();
+                Err(tf(Msg::PageNotFoundAvailable, &[&args, &pages.join(t(Msg::ListSeparator))]))
+            }
+        },
+        "mode" if MODES.contains(&args) => Ok(AppEvent::SetMode(args.into())),
+        "mode" => Err(tf(Msg::ModeNotFound, &[&args, &MODES.join(t(Msg::ListSeparator))])),
+        "select" => {
+            let groups = ctx.groups();
+            let (group, proxy) = groups
+                .iter()
+                .find_map(|group| {
+                    let proxy = args.strip_prefix(group)?.strip_prefix(' ')?;
+                    Some((*group, proxy.trim()))
+                })
+                .ok_or_else(|| tf(Msg::GroupNotFound, &[&args]))?;
+            if !ctx.group_members(group).iter().any(|x| x == proxy) {
+                return Err(tf(Msg::ProxyNotInGroup, &[&group, &proxy]));
+            }
+            Ok(AppEvent::SelectProxy(group.into(), proxy.into()))
+        }
+        "test" if ctx.groups().contains(&args) => Ok(AppEvent::TestDelay(args.into())),
+        "test" => Err(tf(Msg::GroupNotFound, &[&args])),
+        "close-conn" if !args.is_empty() => Ok(AppEvent::CloseConnections(args.into())),
+        "close-conn" => Err(t(Msg::CloseConnUsage).into()),
+        "profile" if ctx.backends.iter().any(|x| x == args) => Ok(AppEvent::SelectBackend(args.into())),
+        "profile" => Err(tf(Msg::BackendNotFound, &[&args])),
+        "quit" => Ok(AppEvent::Quit),
+        _ => Err(tf(Msg::UnknownCommand, &[&name])),
+    }
+}
+
+// 模糊匹配，pattern中的字符按顺序出现在text中时返回跳过的字符数，越小越匹配
+pub fn fuzzy_match(pattern: &str, text: &str) -> Option<usize> {
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    let mut pos = 0;
+    let mut score = 0;
+    for c in pattern.to_lowercase().chars() {
+        let i = text[pos..].iter().position(|x| *x == c)?;
+        score += i;
+        pos += i + 1;
+    }
+    Some(score)
+}
@@ -1,38 +1,196 @@
-use ratatui::prelude::Color;
-use ratatui::style::palette::tailwind;
-use std::sync::LazyLock;
-
-#[derive(Debug, Clone)]
-pub struct ThemeColors {
-    pub buffer_bg: Color,
-    pub header_bg: Color,
-    pub header_fg: Color,
-    pub row_fg: Color,
-    pub selected_style_fg: Color,
-    pub normal_row_color: Color,
-    pub alt_row_color: Color,
+use std::borrow::Cow;
+use std::ops::Range;
+use chrono::{DateTime, Local};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+// 省略号，截断过长的内容时使用
+pub const ELLIPSIS: &str = "…";
+
+// 一个字形簇(国旗、ZWJ组合的emoji、带组合符号的字母等)的显示宽度，与ratatui绘制时的计算方式一致
+fn grapheme_width(g: &str) -> usize {
+    if g.contains(char::is_control) {
+        return 0;
+    }
+    g.width()
+}
+
+// 字符串在终端中的显示宽度
+pub fn string_width(s: &str) -> usize {
+    s.graphemes(true).map(grapheme_width).sum()
+}
+
+// 截断到不超过width列，超出时末尾显示省略号
+pub fn truncate(s: &str, width: usize) -> Cow<'_, str> {
+    if string_width(s) <= width {
+        return Cow::Borrowed(s);
+    }
+    if width == 0 {
+        return Cow::Borrowed("");
+    }
+    let mut result = String::new();
+    let mut used = 0;
+    for g in s.graphemes(true) {
+        let w = grapheme_width(g);
+        if used + w > width - 1 {
+            break;
+        }
+        used += w;
+        result.push_str(g);
+    }
+    result.push_str(ELLIPSIS);
+    Cow::Owned(result)
 }
 
-pub static COLOR: LazyLock<ThemeColors> = LazyLock::new(|| {
-    ThemeColors {
-        buffer_bg: tailwind::SLATE.c950,
-        header_bg: tailwind::BLUE.c900,
-        header_fg: tailwind::SLATE.c200,
-        row_fg: tailwind::SLATE.c200,
-        selected_style_fg: tailwind::BLUE.c400,
-        normal_row_color: tailwind::SLATE.c950,
-        alt_row_color: tailwind::SLATE.c900,
+// 按显示宽度折行，优先在空白处断开，单词太长时强制断开
+// 返回每一行在s中的位置，用于把s中的位置(比如高亮)对应到折行后的行
+pub fn wrap_ranges(s: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let mut lines = vec![];
+    // 当前行为s[start..end]
+    let mut start = 0;
+    let mut end = 0;
+    let mut line_width = 0;
+    // 当前行最后一个空白之后的位置和宽度，折行时从这里断开
+    let mut brk: Option<(usize, usize)> = None;
+    for (pos, g) in s.grapheme_indices(true) {
+        let w = grapheme_width(g);
+        while line_width + w > width && end > start {
+            match brk.take() {
+                Some((pos, pos_width)) => {
+                    // 断开处之前只有空白时不产生空行
+                    let line = start..start + s[start..pos].trim_end().len();
+                    if !line.is_empty() {
+                        lines.push(line);
+                    }
+                    start = pos;
+                    line_width -= pos_width;
+                }
+                None => {
+                    lines.push(start..end);
+                    start = end;
+                    line_width = 0;
+                }
+            }
+        }
+        // 行首的空白不显示
+        if start == end && g.trim().is_empty() && !lines.is_empty() {
+            start = pos + g.len();
+            end = start;
+            continue;
+        }
+        end = pos + g.len();
+        line_width += w;
+        if g.trim().is_empty() {
+            brk = Some((end, line_width));
+        }
+    }
+    if end > start || lines.is_empty() {
+        lines.push(start..end);
+    }
+    lines
+}
+
+
+// 接口返回的RFC 3339时间转换为本地时间显示，格式不对时原样返回
+pub fn local_time(s: &str) -> String {
+    match DateTime::parse_from_rfc3339(s) {
+        Ok(x) => x.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string(),
+        Err(_) => s.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FAMILY: &str = "👨\u{200d}👩\u{200d}👧";
+
+    #[test]
+    fn width() {
+        assert_eq!(string_width("abc"), 3);
+        assert_eq!(string_width("中文"), 4);
+        // ZWJ组合的emoji和带组合符号的字母各算一个字形簇
+        assert_eq!(string_width(FAMILY), 2);
+        assert_eq!(string_width("e\u{301}"), 1);
+        assert_eq!(string_width("a\tb"), 2);
     }
-});
 
+    #[test]
+    fn truncate_ascii() {
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("abcd", 3), "ab…");
+        assert!(matches!(truncate("abc", 5), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn truncate_narrow() {
+        assert_eq!(truncate("abc", 0), "");
+        assert_eq!(truncate("abc", 1), "…");
+        assert_eq!(truncate("", 0), "");
+        assert_eq!(truncate("a", 1), "a");
+    }
+
+    #[test]
+    fn truncate_wide() {
+        assert_eq!(truncate("中文字符串", 5), "中文…");
+        // 放不下整个宽字符时不截断到半个字符，宽度可以小于width
+        assert_eq!(truncate("中文字符串", 4), "中…");
+        assert_eq!(string_width(&truncate("中文字符串", 4)), 3);
+        assert_eq!(truncate("中文", 2), "…");
+    }
+
+    #[test]
+    fn truncate_graphemes() {
+        assert_eq!(truncate(&format!("{FAMILY}xyz"), 3), format!("{FAMILY}…"));
+        assert_eq!(truncate(&format!("{FAMILY}x"), 2), "…");
+        assert_eq!(truncate("e\u{301}e\u{301}e", 2), "e\u{301}…");
+    }
+
+    fn wrap(s: &str, width: usize) -> Vec<&str> {
+        wrap_ranges(s, width).into_iter().map(|x| &s[x]).collect()
+    }
+
+    #[test]
+    fn wrap_at_whitespace() {
+        assert_eq!(wrap("hello world foo", 7), ["hello", "world", "foo"]);
+        assert_eq!(wrap("hello world", 11), ["hello world"]);
+        assert_eq!(wrap("ab  cd", 2), ["ab", "cd"]);
+    }
+
+    #[test]
+    fn wrap_long_word() {
+        // 没有空白可以断开时按宽度硬折行
+        assert_eq!(wrap("abcdefghij", 4), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("ab abcdefgh", 4), ["ab", "abcd", "efgh"]);
+    }
+
+    #[test]
+    fn wrap_leading_whitespace() {
+        assert_eq!(wrap("  lead  space", 4), ["lead", "spac", "e"]);
+        assert_eq!(wrap("  ab", 4), ["  ab"]);
+        assert_eq!(wrap("ab      cd", 4), ["ab", "cd"]);
+    }
+
+    #[test]
+    fn wrap_wide() {
+        assert_eq!(wrap("中文字符", 4), ["中文", "字符"]);
+        assert_eq!(wrap("中文字", 3), ["中", "文", "字"]);
+        // 宽字符放不下时也占一行
+        assert_eq!(wrap("中文", 1), ["中", "文"]);
+        assert_eq!(wrap(&format!("{FAMILY}{FAMILY}"), 3), [FAMILY, FAMILY]);
+    }
+
+    #[test]
+    fn wrap_narrow() {
+        assert_eq!(wrap("abc", 1), ["a", "b", "c"]);
+        assert_eq!(wrap("abc", 0), ["a", "b", "c"]);
+        assert_eq!(wrap("e\u{301}e\u{301}", 1), ["e\u{301}", "e\u{301}"]);
+    }
 
-pub fn string_width(s: &str) -> u32 {
-    let mut len = 0;
-    for c in s.chars() {
-        len += match c as u32 {
-            0..=0xff => 1,
-            _ => 2,
-        };
+    #[test]
+    fn wrap_empty() {
+        assert_eq!(wrap("", 4), [""]);
+        assert_eq!(wrap("", 0), [""]);
     }
-    len
 }
@@ -0,0 +1,547 @@
+use std::env;
+use std::fmt::Display;
+use std::sync::{LazyLock, RwLock};
+use serde::{Deserialize, Serialize};
+
+// 配置文件中的language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum Language {
+    // 根据环境变量LC_ALL、LC_MESSAGES、LANG选择
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "zh-CN", alias = "zh")]
+    Zh,
+    #[serde(rename = "en")]
+    En,
+}
+
+// 实际使用的语言
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+pub fn resolve(language: Language) -> Lang {
+    match language {
+        Language::Auto => lang_from_env(),
+        Language::Zh => Lang::Zh,
+        Language::En => Lang::En,
+    }
+}
+
+// 与gettext的顺序一致，没有设置时使用中文，C/POSIX和其他语言使用英文
+fn lang_from_env() -> Lang {
+    let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|x| env::var(x).ok())
+        .find(|x| !x.is_empty());
+    match value {
+        Some(x) if x.to_lowercase().starts_with("zh") => Lang::Zh,
+        Some(_) => Lang::En,
+        None => Lang::Zh,
+    }
+}
+
+pub fn set_lang(lang: Lang) {
+    *LANG.write().unwrap() = lang;
+}
+
+static LANG: LazyLock<RwLock<Lang>> = LazyLock::new(|| RwLock::new(lang_from_env()));
+
+// 界面上显示的所有文字，新的文字在这里添加并在catalog中给出各语言的翻译
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    // 命令行
+    CliAbout,
+    CliHost,
+    CliSecret,
+    CliConfig,
+    CliProfile,
+    CliPage,
+    CliLogLevel,
+    CliPrintConfig,
+    CliLegacyProfile,
+
+    Ready,
+    Help,
+    PressAnyKey,
+    PageNotFound,
+    BackendSwitched,
+    ConfigReloaded,
+    ConfigReloadedWithWarnings,
+    ConfigReloadError,
+    LoadConfigError,
+    ConnectBackendError,
+
+    // 界面名称，同时用于菜单
+    Proxies,
+    Group,
+    Logs,
+    Connections,
+    Backends,
+
+    // 菜单
+    Search,
+    Quit,
+    View,
+    Open,
+    Select,
+    Back,
+    TestDelay,
+    Pause,
+    Resume,
+    Confirm,
+    Discard,
+    ModeRule,
+    ModeGlobal,
+    ModeDirect,
+    Filter,
+    ColumnsTitle,
+    ShowColumn,
+    MoveUp,
+    MoveDown,
+    Pin,
+    Wider,
+    Narrower,
+    AutoWidth,
+    Reset,
+    Close,
+
+    // 列设置
+    ColumnAuto,
+    ColumnPinned,
+    LoadColumnsError,
+    SaveColumnsError,
+
+    // 过滤条件
+    FilterUnknownColumn,
+    FilterMissingValue,
+    FilterBadNumber,
+    FilterUnclosedRegex,
+    FilterBadRegex,
+    FilterUnclosedQuote,
+
+    // 表头
+    Name,
+    Detail,
+    Delay,
+    Current,
+    Address,
+    Mode,
+    CurrentNode,
+    DownloadSpeed,
+    UploadSpeed,
+    ConnectionCount,
+    Status,
+    SourceHost,
+    Host,
+    Chain,
+    Downloaded,
+    Uploaded,
+    Type,
+    ConnectedTime,
+    Rule,
+
+    // 多后端总览
+    BackendConnecting,
+    BackendOnline,
+    BackendOffline,
+    Timeout,
+
+    // 后台操作
+    SetModeError,
+    LoadDataError,
+    SelectProxyError,
+    Testing,
+    TestDelayError,
+    TestDone,
+    ClosedAll,
+    ClosedCount,
+    CloseError,
+
+    // 多选
+    MarkedCount,
+    CopiedCount,
+    CopyError,
+
+    // 详情
+    Copy,
+    CopiedValue,
+    DetailGeneral,
+    DetailMembers,
+    DetailHistory,
+    DetailId,
+    DetailSniffHost,
+    DetailDestination,
+    DetailInbound,
+    DetailNetwork,
+    DetailRulePayload,
+    DetailStart,
+    DetailMessage,
+
+    // 日志和链接
+    ConnectFailRetry,
+    Disconnected,
+    ConnectionDataError,
+    Traffic,
+    TimeFormatError,
+    JustNow,
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+
+    // 命令面板
+    ListSeparator,
+    PageNotFoundAvailable,
+    ModeNotFound,
+    GroupNotFound,
+    ProxyNotInGroup,
+    CloseConnUsage,
+    BackendNotFound,
+    UnknownCommand,
+
+    // 配置
+    ConfigNotFound,
+    BackendWarning,
+    ReadKeyFileError,
+    KeyCommandError,
+    KeyCommandTimeout,
+    KeyFilePermissive,
+    UnknownConfig,
+    ProfileNotFound,
+    InvalidKey,
+    ThemeBaseNotFound,
+    ThemeInvalidColor,
+    ThemeNotFound,
+    ThemeNoTrueColor,
+    PrintConfigPath,
+    PrintConfigDefault,
+
+    // 后端接口
+    AuthFailed,
+    HttpError,
+    UnknownError,
+
+    // 帮助中的分类
+    CategoryMove,
+    CategoryPage,
+    CategoryOperation,
+    CategoryFilter,
+    CategoryEdit,
+    CategoryColumns,
+
+    // 帮助中动作的说明
+    ActionUp,
+    ActionDown,
+    ActionPageUp,
+    ActionPageDown,
+    ActionTop,
+    ActionBottom,
+    ActionScrollLeft,
+    ActionScrollRight,
+    ActionSelect,
+    ActionBack,
+    ActionQuit,
+    ActionShowProxy,
+    ActionShowLog,
+    ActionShowConnection,
+    ActionShowBackend,
+    ActionNextPage,
+    ActionPrevPage,
+    ActionGotoPage,
+    ActionPrevious,
+    ActionSwitchMode,
+    ActionTestDelay,
+    ActionPause,
+    ActionMark,
+    ActionMarkRange,
+    ActionMarkAll,
+    ActionCopy,
+    ActionCloseConnection,
+    ActionDetail,
+    ActionDetailClose,
+    ActionSort,
+    ActionSortReverse,
+    ActionColumns,
+    ActionHelp,
+    ActionCommand,
+    ActionFilter,
+    ActionFilterConfirm,
+    ActionFilterCancel,
+    ActionColumnToggle,
+    ActionColumnMoveUp,
+    ActionColumnMoveDown,
+    ActionColumnPin,
+    ActionColumnWider,
+    ActionColumnNarrower,
+    ActionColumnAutoWidth,
+    ActionColumnReset,
+    ActionColumnsClose,
+    ActionHistoryPrev,
+    ActionHistoryNext,
+    ActionCursorLeft,
+    ActionCursorRight,
+    ActionCursorHome,
+    ActionCursorEnd,
+    ActionDeleteBack,
+    ActionDeleteForward,
+    ActionDeleteWord,
+    ActionDeleteToStart,
+    ActionDeleteToEnd,
+}
+
+// 中文，英文
+fn catalog(msg: Msg) -> (&'static str, &'static str) {
+    match msg {
+        Msg::CliAbout => ("clash终端面板", "Terminal dashboard for clash"),
+        Msg::CliHost => ("clash的external-controller地址，例如127.0.0.1:9090", "Address of the clash external-controller, e.g. 127.0.0.1:9090"),
+        Msg::CliSecret => ("clash的secret，命令行参数可能被其他用户看到，建议使用环境变量", "Secret of clash; command-line arguments may be visible to other users, prefer the environment variable"),
+        Msg::CliConfig => ("配置文件路径，默认依次查找程序所在目录、启动目录、$XDG_CONFIG_HOME/clash-tui/、~/.config/clash-tui/下的clash-tui.ini/toml/yaml", "Config file path; by default clash-tui.ini/toml/yaml is searched in the program directory, the working directory, $XDG_CONFIG_HOME/clash-tui/ and ~/.config/clash-tui/"),
+        Msg::CliProfile => ("使用配置文件中的哪个后端分节", "Backend section of the config file to use"),
+        Msg::CliPage => ("启动后显示的界面", "Page shown at startup"),
+        Msg::CliLogLevel => ("日志界面订阅的日志级别", "Log level subscribed by the log page"),
+        Msg::CliPrintConfig => ("输出合并后的配置并退出", "Print the merged config and exit"),
+        Msg::CliLegacyProfile => ("兼容旧用法：第一个参数为后端分节名称", "Legacy usage: backend section name as the first argument"),
+
+        Msg::Ready => ("就绪", "Ready"),
+        Msg::Help => ("帮助", "Help"),
+        Msg::PressAnyKey => ("按任意键关闭", "Press any key to close"),
+        Msg::PageNotFound => ("界面不存在: {}", "No such page: {}"),
+        Msg::BackendSwitched => ("后端: {}", "Backend: {}"),
+        Msg::ConfigReloaded => ("配置已重新加载", "Configuration reloaded"),
+        Msg::ConfigReloadedWithWarnings => ("配置已重新加载: {}", "Configuration reloaded: {}"),
+        Msg::ConfigReloadError => ("重新加载配置出错，继续使用原配置: {}", "Failed to reload configuration, keeping the previous one: {}"),
+        Msg::LoadConfigError => ("加载配置文件出错", "Failed to load the configuration file"),
+        Msg::ConnectBackendError => ("连接后端出错，请确认配置是否正确", "Failed to connect to the backend, please check the configuration"),
+
+        Msg::Proxies => ("代理", "Proxies"),
+        Msg::Group => ("分组", "Group"),
+        Msg::Logs => ("日志", "Logs"),
+        Msg::Connections => ("链接", "Connections"),
+        Msg::Backends => ("后端", "Backends"),
+
+        Msg::Search => ("搜索", "Search"),
+        Msg::Quit => ("退出", "Quit"),
+        Msg::View => ("查看", "View"),
+        Msg::Open => ("进入", "Open"),
+        Msg::Select => ("选择", "Select"),
+        Msg::Back => ("返回", "Back"),
+        Msg::TestDelay => ("测速", "Test"),
+        Msg::Pause => ("暂停", "Pause"),
+        Msg::Resume => ("恢复", "Resume"),
+        Msg::Confirm => ("确认", "Confirm"),
+        Msg::Discard => ("放弃", "Discard"),
+        Msg::ModeRule => ("模式:RULE", "Mode:RULE"),
+        Msg::ModeGlobal => ("模式:GLOBAL", "Mode:GLOBAL"),
+        Msg::ModeDirect => ("模式:DIRECT", "Mode:DIRECT"),
+        Msg::Filter => ("过滤: {}", "Filter: {}"),
+        Msg::ColumnsTitle => ("列", "Columns"),
+        Msg::ShowColumn => ("显示", "Show"),
+        Msg::MoveUp => ("上移", "Up"),
+        Msg::MoveDown => ("下移", "Down"),
+        Msg::Pin => ("固定", "Pin"),
+        Msg::Wider => ("加宽", "Wider"),
+        Msg::Narrower => ("减窄", "Narrower"),
+        Msg::AutoWidth => ("自动宽度", "Auto width"),
+        Msg::Reset => ("重置", "Reset"),
+        Msg::Close => ("关闭", "Close"),
+
+        Msg::ColumnAuto => ("自动", "auto"),
+        Msg::ColumnPinned => ("固定", "pinned"),
+        Msg::LoadColumnsError => ("读取列设置出错，使用默认设置: {}", "Failed to load the column layout, using defaults: {}"),
+        Msg::SaveColumnsError => ("保存列设置出错: {}", "Failed to save the column layout: {}"),
+
+        Msg::FilterUnknownColumn => ("没有{}列，可用的列: {}", "No column {}, available: {}"),
+        Msg::FilterMissingValue => ("{}后面缺少条件", "Missing a value after {}"),
+        Msg::FilterBadNumber => ("{}的数值无效: {}", "Invalid number for {}: {}"),
+        Msg::FilterUnclosedRegex => ("正则表达式缺少结尾的/", "Regex is missing the closing /"),
+        Msg::FilterBadRegex => ("正则表达式无效: {}: {}", "Invalid regex {}: {}"),
+        Msg::FilterUnclosedQuote => ("引号没有闭合", "Unclosed quote"),
+
+        Msg::Name => ("名称", "Name"),
+        Msg::Detail => ("详情", "Detail"),
+        Msg::Delay => ("延迟", "Delay"),
+        Msg::Current => ("选中", "Current"),
+        Msg::Address => ("地址", "Address"),
+        Msg::Mode => ("模式", "Mode"),
+        Msg::CurrentNode => ("当前节点", "Current node"),
+        Msg::DownloadSpeed => ("下载速度", "Down speed"),
+        Msg::UploadSpeed => ("上传速度", "Up speed"),
+        Msg::ConnectionCount => ("连接数", "Connections"),
+        Msg::Status => ("状态", "Status"),
+        Msg::SourceHost => ("源主机", "Source"),
+        Msg::Host => ("主机", "Host"),
+        Msg::Chain => ("链路", "Chain"),
+        Msg::Downloaded => ("下载量", "Downloaded"),
+        Msg::Uploaded => ("上传量", "Uploaded"),
+        Msg::Type => ("类型", "Type"),
+        Msg::ConnectedTime => ("连接时间", "Duration"),
+        Msg::Rule => ("规则", "Rule"),
+
+        Msg::BackendConnecting => ("连接中", "Connecting"),
+        Msg::BackendOnline => ("在线", "Online"),
+        Msg::BackendOffline => ("离线: {}", "Offline: {}"),
+        Msg::Timeout => ("连接超时", "Connection timed out"),
+
+        Msg::SetModeError => ("设置模式出错: {}", "Failed to set mode: {}"),
+        Msg::LoadDataError => ("加载数据出错: {}", "Failed to load data: {}"),
+        Msg::SelectProxyError => ("设置分组出错: {}", "Failed to select proxy: {}"),
+        Msg::Testing => ("测速中...", "Testing..."),
+        Msg::TestDelayError => ("检查延时出错: {}", "Failed to test delay: {}"),
+        Msg::TestDone => ("测速完成", "Delay test finished"),
+        Msg::ClosedAll => ("已关闭所有链接", "Closed all connections"),
+        Msg::ClosedCount => ("已关闭{}个链接", "Closed {} connections"),
+        Msg::CloseError => ("关闭链接出错: {}", "Failed to close connections: {}"),
+
+        Msg::MarkedCount => ("已选择{}行", "{} rows selected"),
+        Msg::CopiedCount => ("已发送{}行到终端剪贴板", "Sent {} rows to the terminal clipboard"),
+        Msg::CopyError => ("复制出错: {}", "Failed to copy: {}"),
+
+        Msg::Copy => ("复制", "Copy"),
+        Msg::CopiedValue => ("已发送{}到终端剪贴板", "Sent {} to the terminal clipboard"),
+        Msg::DetailGeneral => ("基本信息", "General"),
+        Msg::DetailMembers => ("分组中的节点({})", "Members ({})"),
+        Msg::DetailHistory => ("测速记录", "Delay history"),
+        Msg::DetailId => ("ID", "ID"),
+        Msg::DetailSniffHost => ("嗅探域名", "Sniffed host"),
+        Msg::DetailDestination => ("目标地址", "Destination"),
+        Msg::DetailInbound => ("入站", "Inbound"),
+        Msg::DetailNetwork => ("网络", "Network"),
+        Msg::DetailRulePayload => ("规则内容", "Rule payload"),
+        Msg::DetailStart => ("开始时间", "Start time"),
+        Msg::DetailMessage => ("内容", "Message"),
+
+        Msg::ConnectFailRetry => ("连接失败，重试: {}", "Connection failed, retrying: {}"),
+        Msg::Disconnected => ("连接已断开，重连", "Disconnected, reconnecting"),
+        Msg::ConnectionDataError => ("处理连接数据出错: {}", "Failed to process connection data: {}"),
+        Msg::Traffic => ("下载速度:{}|上传速度:{}|总下载:{}|总上传:{}", "Down:{}|Up:{}|Total down:{}|Total up:{}"),
+        Msg::TimeFormatError => ("格式化时间错误: {} {}", "Invalid time: {} {}"),
+        Msg::JustNow => ("几秒前", "just now"),
+        Msg::Days => ("天", "d"),
+        Msg::Hours => ("小时", "h"),
+        Msg::Minutes => ("分", "m"),
+        Msg::Seconds => ("秒", "s"),
+
+        Msg::ListSeparator => ("、", ", "),
+        Msg::PageNotFoundAvailable => ("界面不存在: {}，可用: {}", "No such page: {}, available: {}"),
+        Msg::ModeNotFound => ("模式不存在: {}，可用: {}", "No such mode: {}, available: {}"),
+        Msg::GroupNotFound => ("分组不存在: {}", "No such group: {}"),
+        Msg::ProxyNotInGroup => ("分组{}中没有节点: {}", "Group {} has no proxy: {}"),
+        Msg::CloseConnUsage => ("用法: close-conn all|id:链接ID|host:主机|关键字", "Usage: close-conn all|id:ID|host:HOST|KEYWORD"),
+        Msg::BackendNotFound => ("后端不存在: {}", "No such backend: {}"),
+        Msg::UnknownCommand => ("未知命令: {}", "Unknown command: {}"),
+
+        Msg::ConfigNotFound => ("配置文件不存在: {}", "Configuration file not found: {}"),
+        Msg::BackendWarning => ("后端{}: {}", "Backend {}: {}"),
+        Msg::ReadKeyFileError => ("后端{}读取key_file出错: {}", "Backend {}: failed to read key_file: {}"),
+        Msg::KeyCommandError => ("后端{}执行key_command出错: {}", "Backend {}: failed to run key_command: {}"),
+        Msg::KeyCommandTimeout => ("{}秒内没有结束", "did not finish within {} seconds"),
+        Msg::KeyFilePermissive => ("{}的权限过于宽松({})，建议改为600", "{} is too permissive ({}), consider 600"),
+        Msg::UnknownConfig => ("未知配置项: {}", "Unknown setting: {}"),
+        Msg::ProfileNotFound => ("配置文件中没有找到后端: {}", "Backend not found in configuration: {}"),
+        Msg::InvalidKey => ("按键无效: {}", "Invalid key: {}"),
+        Msg::ThemeBaseNotFound => ("主题{}的base不存在: {}", "Theme {}: no such base: {}"),
+        Msg::ThemeInvalidColor => ("主题{}的颜色无效: {}", "Theme {}: invalid color: {}"),
+        Msg::ThemeNotFound => ("主题不存在: {}", "No such theme: {}"),
+        Msg::ThemeNoTrueColor => ("终端可能不支持真彩色，主题{}的颜色可能显示不正确，可以使用basic或auto", "The terminal may not support true color, theme {} may look wrong; try basic or auto"),
+        Msg::PrintConfigPath => ("# 配置文件: {}", "# Configuration file: {}"),
+        Msg::PrintConfigDefault => ("# 未找到配置文件，使用默认值", "# No configuration file found, using defaults"),
+
+        Msg::AuthFailed => ("认证失败：请确认key是否正确", "Authentication failed: please check the key"),
+        Msg::HttpError => ("http请求错误: code={}, resp={}", "HTTP request failed: code={}, resp={}"),
+        Msg::UnknownError => ("未知错误", "unknown error"),
+
+        Msg::CategoryMove => ("移动", "Move"),
+        Msg::CategoryPage => ("界面", "Pages"),
+        Msg::CategoryOperation => ("操作", "Actions"),
+        Msg::CategoryFilter => ("过滤", "Filter"),
+        Msg::CategoryEdit => ("编辑", "Editing"),
+        Msg::CategoryColumns => ("列设置", "Columns"),
+
+        Msg::ActionUp => ("上一行", "Previous row"),
+        Msg::ActionDown => ("下一行", "Next row"),
+        Msg::ActionPageUp => ("上一页", "Previous page"),
+        Msg::ActionPageDown => ("下一页", "Next page"),
+        Msg::ActionTop => ("跳到开头", "Go to top"),
+        Msg::ActionBottom => ("跳到末尾", "Go to bottom"),
+        Msg::ActionScrollLeft => ("表格向左滚动一列", "Scroll the table one column left"),
+        Msg::ActionScrollRight => ("表格向右滚动一列", "Scroll the table one column right"),
+        Msg::ActionSelect => ("打开或选择当前行", "Open or select the current row"),
+        Msg::ActionBack => ("返回上一级", "Go back"),
+        Msg::ActionQuit => ("退出", "Quit"),
+        Msg::ActionShowProxy => ("代理界面", "Proxies page"),
+        Msg::ActionShowLog => ("日志界面", "Logs page"),
+        Msg::ActionShowConnection => ("链接界面", "Connections page"),
+        Msg::ActionShowBackend => ("多后端总览", "Backends overview"),
+        Msg::ActionNextPage => ("下一个标签页", "Next tab"),
+        Msg::ActionPrevPage => ("上一个标签页", "Previous tab"),
+        Msg::ActionGotoPage => ("第N个按键切换到第N个标签页", "The Nth key switches to the Nth tab"),
+        Msg::ActionPrevious => ("回到之前的界面", "Return to the previous page"),
+        Msg::ActionSwitchMode => ("切换代理模式", "Switch proxy mode"),
+        Msg::ActionTestDelay => ("测试分组中所有节点的延迟，选择了节点时只测试选择的节点", "Test the delay of every proxy in the group, or only the selected ones"),
+        Msg::ActionPause => ("暂停或恢复刷新", "Pause or resume updates"),
+        Msg::ActionMark => ("选择或取消选择当前行", "Select or unselect the row"),
+        Msg::ActionMarkRange => ("选择上次选择的行到当前行之间的所有行", "Select every row from the last selected one to this one"),
+        Msg::ActionMarkAll => ("选择所有满足过滤条件的行，已经全部选择时取消选择", "Select every row matching the filter, or clear the selection"),
+        Msg::ActionCopy => ("复制选择的行到剪贴板，没有选择时复制当前行", "Copy the selected rows, or the current row, to the clipboard"),
+        Msg::ActionCloseConnection => ("关闭选择的链接，没有选择时关闭当前链接", "Close the selected connections, or the current one"),
+        Msg::ActionDetail => ("显示当前行的详情，Enter只在界面没有用到它时生效(链接、日志界面)", "Show details of the current row; Enter only where the page has no other use for it (connections, logs)"),
+        Msg::ActionDetailClose => ("关闭详情", "Close the detail popup"),
+        Msg::ActionSort => ("按下一列排序，最后一列之后恢复原来的顺序", "Sort by the next column, then back to the original order"),
+        Msg::ActionSortReverse => ("反转排序方向", "Reverse the sort order"),
+        Msg::ActionColumns => ("选择要显示的列，调整顺序和宽度", "Choose, reorder and resize columns"),
+        Msg::ActionHelp => ("显示或关闭帮助", "Show or close help"),
+        Msg::ActionCommand => ("打开命令面板", "Open the command palette"),
+        Msg::ActionFilter => ("输入过滤条件", "Edit the filter"),
+        Msg::ActionFilterConfirm => ("确认过滤条件", "Apply the filter"),
+        Msg::ActionFilterCancel => ("清除过滤条件", "Clear the filter"),
+        Msg::ActionColumnToggle => ("显示或隐藏当前列", "Show or hide the column"),
+        Msg::ActionColumnMoveUp => ("当前列向前移动", "Move the column left"),
+        Msg::ActionColumnMoveDown => ("当前列向后移动", "Move the column right"),
+        Msg::ActionColumnPin => ("固定当前列，固定的列显示在最前面且不会被压缩", "Pin the column to the left so it is never squeezed"),
+        Msg::ActionColumnWider => ("加宽当前列，改为固定宽度", "Widen the column, giving it a fixed width"),
+        Msg::ActionColumnNarrower => ("减窄当前列，改为固定宽度", "Narrow the column, giving it a fixed width"),
+        Msg::ActionColumnAutoWidth => ("当前列根据内容自动调整宽度", "Size the column to its content"),
+        Msg::ActionColumnReset => ("恢复默认的列设置", "Restore the default columns"),
+        Msg::ActionColumnsClose => ("关闭列设置并保存", "Close and save the column layout"),
+        Msg::ActionHistoryPrev => ("上一条历史", "Previous history entry"),
+        Msg::ActionHistoryNext => ("下一条历史", "Next history entry"),
+        Msg::ActionCursorLeft => ("光标左移", "Move the cursor left"),
+        Msg::ActionCursorRight => ("光标右移", "Move the cursor right"),
+        Msg::ActionCursorHome => ("光标移到开头", "Move the cursor to the start"),
+        Msg::ActionCursorEnd => ("光标移到末尾", "Move the cursor to the end"),
+        Msg::ActionDeleteBack => ("删除光标前的字符", "Delete the character before the cursor"),
+        Msg::ActionDeleteForward => ("删除光标处的字符", "Delete the character at the cursor"),
+        Msg::ActionDeleteWord => ("删除光标前的一个词", "Delete the word before the cursor"),
+        Msg::ActionDeleteToStart => ("删除光标前的内容", "Delete everything before the cursor"),
+        Msg::ActionDeleteToEnd => ("删除光标后的内容", "Delete everything after the cursor"),
+    }
+}
+
+// 当前语言的文字
+pub fn t(msg: Msg) -> &'static str {
+    let (zh, en) = catalog(msg);
+    match *LANG.read().unwrap() {
+        Lang::Zh => zh,
+        Lang::En => en,
+    }
+}
+
+// 当前语言的文字，依次用args替换其中的{}
+pub fn tf(msg: Msg, args: &[&dyn Display]) -> String {
+    let mut result = String::new();
+    let mut args = args.iter();
+    let mut parts = t(msg).split("{}");
+    result.push_str(parts.next().unwrap_or_default());
+    for part in parts {
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
@@ -0,0 +1,501 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use indexmap::IndexMap;
+use crate::i18n::{t, tf, Msg};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    ScrollLeft,
+    ScrollRight,
+    Select,
+    Back,
+    Quit,
+    ShowProxy,
+    ShowLog,
+    ShowConnection,
+    ShowBackend,
+    NextPage,
+    PrevPage,
+    GotoPage,
+    Previous,
+    SwitchMode,
+    TestDelay,
+    Pause,
+    Mark,
+    MarkRange,
+    MarkAll,
+    Copy,
+    CloseConnection,
+    Sort,
+    SortReverse,
+    Detail,
+    DetailClose,
+    Columns,
+    ColumnToggle,
+    ColumnMoveUp,
+    ColumnMoveDown,
+    ColumnPin,
+    ColumnWider,
+    ColumnNarrower,
+    ColumnAutoWidth,
+    ColumnReset,
+    ColumnsClose,
+    Help,
+    Command,
+    Filter,
+    FilterConfirm,
+    FilterCancel,
+    HistoryPrev,
+    HistoryNext,
+    CursorLeft,
+    CursorRight,
+    CursorHome,
+    CursorEnd,
+    DeleteBack,
+    DeleteForward,
+    DeleteWord,
+    DeleteToStart,
+    DeleteToEnd,
+}
+
+// vim风格的按键，启用vim_keys时追加到默认按键之后
+const VIM_KEYS: [(Action, &str); 6] = [
+    (Action::Up, "k"),
+    (Action::Down, "j"),
+    (Action::PageUp, "ctrl-u"),
+    (Action::PageDown, "ctrl-d"),
+    (Action::Top, "g"),
+    (Action::Bottom, "G"),
+];
+
+// 帮助中的分类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Move,
+    Page,
+    Operation,
+    Filter,
+    Edit,
+    Columns,
+}
+
+impl Category {
+    const ALL: [Category; 6] = [
+        Category::Move,
+        Category::Page,
+        Category::Operation,
+        Category::Filter,
+        Category::Edit,
+        Category::Columns,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Category::Move => t(Msg::CategoryMove),
+            Category::Page => t(Msg::CategoryPage),
+            Category::Operation => t(Msg::CategoryOperation),
+            Category::Filter => t(Msg::CategoryFilter),
+            Category::Edit => t(Msg::CategoryEdit),
+            Category::Columns => t(Msg::CategoryColumns),
+        }
+    }
+}
+
+// 动作，配置文件中使用的名称，默认按键，分类，帮助中的说明
+const ACTIONS: [(Action, &str, &str, Category, Msg); 57] = [
+    (Action::Up, "up", "up", Category::Move, Msg::ActionUp),
+    (Action::Down, "down", "down", Category::Move, Msg::ActionDown),
+    (Action::PageUp, "page_up", "pgup", Category::Move, Msg::ActionPageUp),
+    (Action::PageDown, "page_down", "pgdn", Category::Move, Msg::ActionPageDown),
+    (Action::Top, "top", "home", Category::Move, Msg::ActionTop),
+    (Action::Bottom, "bottom", "end", Category::Move, Msg::ActionBottom),
+    (Action::ScrollLeft, "scroll_left", "left", Category::Move, Msg::ActionScrollLeft),
+    (Action::ScrollRight, "scroll_right", "right", Category::Move, Msg::ActionScrollRight),
+    (Action::Select, "select", "enter", Category::Operation, Msg::ActionSelect),
+    (Action::Back, "back", "esc", Category::Page, Msg::ActionBack),
+    (Action::Quit, "quit", "esc", Category::Page, Msg::ActionQuit),
+    (Action::ShowProxy, "proxy", "P,p", Category::Page, Msg::ActionShowProxy),
+    (Action::ShowLog, "log", "L,l", Category::Page, Msg::ActionShowLog),
+    (Action::ShowConnection, "connection", "C,c", Category::Page, Msg::ActionShowConnection),
+    (Action::ShowBackend, "backend", "B,b", Category::Page, Msg::ActionShowBackend),
+    (Action::NextPage, "next_page", "tab", Category::Page, Msg::ActionNextPage),
+    (Action::PrevPage, "prev_page", "shift-tab", Category::Page, Msg::ActionPrevPage),
+    (Action::GotoPage, "goto_page", "1,2,3,4,5,6,7,8,9", Category::Page, Msg::ActionGotoPage),
+    (Action::Previous, "previous", "backspace", Category::Page, Msg::ActionPrevious),
+    (Action::SwitchMode, "mode", "M,m", Category::Operation, Msg::ActionSwitchMode),
+    (Action::TestDelay, "test_delay", "ctrl-t", Category::Operation, Msg::ActionTestDelay),
+    (Action::Pause, "pause", "z", Category::Operation, Msg::ActionPause),
+    (Action::Mark, "mark", "space", Category::Operation, Msg::ActionMark),
+    (Action::MarkRange, "mark_range", "V", Category::Operation, Msg::ActionMarkRange),
+    (Action::MarkAll, "mark_all", "*", Category::Operation, Msg::ActionMarkAll),
+    (Action::Copy, "copy", "y", Category::Operation, Msg::ActionCopy),
+    (Action::CloseConnection, "close_connection", "x,delete", Category::Operation, Msg::ActionCloseConnection),
+    (Action::Sort, "sort", "s", Category::Operation, Msg::ActionSort),
+    (Action::SortReverse, "sort_reverse", "S", Category::Operation, Msg::ActionSortReverse),
+    (Action::Detail, "detail", "i,enter", Category::Operation, Msg::ActionDetail),
+    (Action::DetailClose, "detail_close", "esc,enter,i", Category::Operation, Msg::ActionDetailClose),
+    (Action::Columns, "columns", "o", Category::Operation, Msg::ActionColumns),
+    (Action::Help, "help", "?,f1", Category::Operation, Msg::ActionHelp),
+    (Action::Command, "command", ":", Category::Operation, Msg::ActionCommand),
+    (Action::Filter, "filter", "/", Category::Filter, Msg::ActionFilter),
+    (Action::FilterConfirm, "filter_confirm", "enter", Category::Filter, Msg::ActionFilterConfirm),
+    (Action::FilterCancel, "filter_cancel", "esc", Category::Filter, Msg::ActionFilterCancel),
+    (Action::HistoryPrev, "history_prev", "up", Category::Edit, Msg::ActionHistoryPrev),
+    (Action::HistoryNext, "history_next", "down", Category::Edit, Msg::ActionHistoryNext),
+    (Action::CursorLeft, "cursor_left", "left", Category::Edit, Msg::ActionCursorLeft),
+    (Action::CursorRight, "cursor_right", "right", Category::Edit, Msg::ActionCursorRight),
+    (Action::CursorHome, "cursor_home", "home,ctrl-a", Category::Edit, Msg::ActionCursorHome),
+    (Action::CursorEnd, "cursor_end", "end,ctrl-e", Category::Edit, Msg::ActionCursorEnd),
+    (Action::DeleteBack, "delete_back", "backspace", Category::Edit, Msg::ActionDeleteBack),
+    (Action::DeleteForward, "delete_forward", "delete", Category::Edit, Msg::ActionDeleteForward),
+    (Action::DeleteWord, "delete_word", "ctrl-w", Category::Edit, Msg::ActionDeleteWord),
+    (Action::DeleteToStart, "delete_to_start", "ctrl-u", Category::Edit, Msg::ActionDeleteToStart),
+    (Action::DeleteToEnd, "delete_to_end", "ctrl-k", Category::Edit, Msg::ActionDeleteToEnd),
+    (Action::ColumnToggle, "column_toggle", "space", Category::Columns, Msg::ActionColumnToggle),
+    (Action::ColumnMoveUp, "column_move_up", "K,shift-up", Category::Columns, Msg::ActionColumnMoveUp),
+    (Action::ColumnMoveDown, "column_move_down", "J,shift-down", Category::Columns, Msg::ActionColumnMoveDown),
+    (Action::ColumnPin, "column_pin", "p", Category::Columns, Msg::ActionColumnPin),
+    (Action::ColumnWider, "column_wider", "+,right", Category::Columns, Msg::ActionColumnWider),
+    (Action::ColumnNarrower, "column_narrower", "-,left", Category::Columns, Msg::ActionColumnNarrower),
+    (Action::ColumnAutoWidth, "column_auto_width", "a", Category::Columns, Msg::ActionColumnAutoWidth),
+    (Action::ColumnReset, "column_reset", "r", Category::Columns, Msg::ActionColumnReset),
+    (Action::ColumnsClose, "columns_close", "esc,enter,o", Category::Columns, Msg::ActionColumnsClose),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    // 解析"ctrl-t"、"esc"、"L"这样的按键描述
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        loop {
+            let lower = rest.to_ascii_lowercase();
+            let (modifier, len) = if lower.starts_with("ctrl-") {
+                (KeyModifiers::CONTROL, 5)
+            } else if lower.starts_with("alt-") {
+                (KeyModifiers::ALT, 4)
+            } else if lower.starts_with("shift-") {
+                (KeyModifiers::SHIFT, 6)
+            } else {
+                break;
+            };
+            modifiers |= modifier;
+            rest = &rest[len..];
+        }
+        let code = match rest.to_lowercase().as_str() {
+            "esc" => KeyCode::Esc,
+            "enter" => KeyCode::Enter,
+            "space" => KeyCode::Char(' '),
+            // 逗号用于分隔多个按键，只能写名称
+            "comma" => KeyCode::Char(','),
+            "tab" if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            f if f.len() > 1 && f.starts_with('f') && f[1..].parse::<u8>().is_ok() => {
+                KeyCode::F(f[1..].parse().unwrap())
+            }
+            _ => {
+                let mut chars = rest.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return None,
+                }
+            }
+        };
+        // 字符按键匹配时忽略shift，"shift-x"写成"X"；其他字符加shift由键盘布局决定，无法匹配
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                let mut upper = c.to_uppercase();
+                match (upper.next(), upper.next()) {
+                    (Some(upper), None) if c.is_alphabetic() => KeyCode::Char(upper),
+                    _ => return None,
+                }
+            }
+            code => code,
+        };
+        Some(Self { code, modifiers })
+    }
+
+    pub fn matches(&self, key_event: &KeyEvent) -> bool {
+        if self.code != key_event.code {
+            return false;
+        }
+        // 字符的大小写和BackTab已经体现了shift，忽略它
+        let mut modifiers = key_event.modifiers;
+        if matches!(key_event.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.modifiers == modifiers
+    }
+
+    // 菜单中显示的名称
+    pub fn name(&self) -> String {
+        let code = match self.code {
+            KeyCode::Esc => "ESC".to_string(),
+            KeyCode::Enter => "ENTER".to_string(),
+            KeyCode::Char(' ') => "<Space>".to_string(),
+            KeyCode::Char(c) if self.modifiers.is_empty() => c.to_string(),
+            KeyCode::Char(c) => c.to_uppercase().to_string(),
+            KeyCode::Tab => "TAB".to_string(),
+            KeyCode::BackTab => "SHIFT-TAB".to_string(),
+            KeyCode::Backspace => "BACKSPACE".to_string(),
+            KeyCode::Delete => "DEL".to_string(),
+            KeyCode::Insert => "INS".to_string(),
+            KeyCode::Up => "UP".to_string(),
+            KeyCode::Down => "DOWN".to_string(),
+            KeyCode::Left => "LEFT".to_string(),
+            KeyCode::Right => "RIGHT".to_string(),
+            KeyCode::Home => "HOME".to_string(),
+            KeyCode::End => "END".to_string(),
+            KeyCode::PageUp => "PGUP".to_string(),
+            KeyCode::PageDown => "PGDN".to_string(),
+            KeyCode::F(n) => format!("F{n}"),
+            _ => "?".to_string(),
+        };
+        let mut name = String::new();
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            name.push_str("CTRL-");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            name.push_str("ALT-");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            name.push_str("SHIFT-");
+        }
+        name.push_str(&code);
+        name
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let bindings = ACTIONS
+            .iter()
+            .map(|(action, _, keys, _, _)| (*action, parse_keys(keys).unwrap()))
+            .collect();
+        Self { bindings }
+    }
+}
+
+fn parse_keys(keys: &str) -> Option<Vec<KeyBinding>> {
+    keys.split(',')
+        .filter(|x| !x.trim().is_empty())
+        .map(KeyBinding::parse)
+        .collect()
+}
+
+impl Keymap {
+    // 配置文件中的按键覆盖默认按键，多个按键用逗号分隔，空字符串表示取消绑定
+    pub fn load(config: &IndexMap<String, String>, vim_keys: bool, warnings: &mut Vec<String>) -> Self {
+        let mut keymap = Self::default();
+        if vim_keys {
+            for (action, key) in VIM_KEYS {
+                keymap.bindings.entry(action).or_default().extend(KeyBinding::parse(key));
+            }
+        }
+        for (name, keys) in config {
+            let Some((action, ..)) = ACTIONS.iter().find(|(_, n, ..)| n == name) else {
+                warnings.push(tf(Msg::UnknownConfig, &[&format!("keybindings.{name}")]));
+                continue;
+            };
+            match parse_keys(keys) {
+                Some(bindings) => {
+                    keymap.bindings.insert(*action, bindings);
+                }
+                None => warnings.push(tf(Msg::InvalidKey, &[&format!("keybindings.{name}={keys}")])),
+            }
+        }
+        keymap
+    }
+
+    pub fn bindings(&self, action: Action) -> &[KeyBinding] {
+        self.bindings.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+pub fn set_keymap(keymap: Keymap) {
+    *KEYMAP.write().unwrap() = keymap;
+}
+
+static KEYMAP: LazyLock<RwLock<Keymap>> = LazyLock::new(|| RwLock::new(Keymap::default()));
+
+// 在actions中按顺序查找与按键匹配的动作
+pub fn find(key_event: &KeyEvent, actions: &[Action]) -> Option<Action> {
+    let keymap = KEYMAP.read().unwrap();
+    actions
+        .iter()
+        .find(|action| keymap.bindings(**action).iter().any(|x| x.matches(key_event)))
+        .copied()
+}
+
+// 与按键匹配的是动作的第几个按键，用于GotoPage这类按键顺序有含义的动作
+pub fn find_index(key_event: &KeyEvent, action: Action) -> Option<usize> {
+    KEYMAP.read().unwrap().bindings(action).iter().position(|x| x.matches(key_event))
+}
+
+// 动作的所有按键的名称
+pub fn key_names(action: Action) -> Vec<String> {
+    KEYMAP.read().unwrap().bindings(action).iter().map(KeyBinding::name).collect()
+}
+
+// 动作的第一个按键的名称，没有绑定按键时返回None
+pub fn key_name(action: Action) -> Option<String> {
+    KEYMAP.read().unwrap().bindings(action).first().map(KeyBinding::name)
+}
+
+// 菜单中的按键名称对应的按键，用于点击菜单
+pub fn key_event(name: &str) -> Option<KeyEvent> {
+    let keymap = KEYMAP.read().unwrap();
+    keymap.bindings
+        .values()
+        .flatten()
+        .find(|x| x.name() == name)
+        .map(|x| KeyEvent::new(x.code, x.modifiers))
+}
+
+// 根据当前生效的按键生成菜单，没有绑定按键的动作不显示
+pub fn menu(items: &[(Action, &'static str)]) -> Vec<(String, &'static str)> {
+    items
+        .iter()
+        .filter_map(|(action, text)| key_name(*action).map(|key| (key, *text)))
+        .collect()
+}
+
+// 按分类生成帮助，每一项为动作的所有按键和说明，没有绑定按键的动作不显示
+pub fn help(actions: &[Action]) -> Vec<(&'static str, Vec<(String, &'static str)>)> {
+    let keymap = KEYMAP.read().unwrap();
+    Category::ALL
+        .iter()
+        .filter_map(|category| {
+            let items: Vec<_> = ACTIONS
+                .iter()
+                .filter(|(action, _, _, c, _)| c == category && actions.contains(action))
+                .filter_map(|(action, _, _, _, text)| {
+                    let bindings = keymap.bindings(*action);
+                    if bindings.is_empty() {
+                        return None;
+                    }
+                    let keys = bindings.iter().map(KeyBinding::name).collect::<Vec<_>>().join("/");
+                    Some((keys, t(*text)))
+                })
+                .collect();
+            (!items.is_empty()).then_some((category.title(), items))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    fn parse(s: &str) -> KeyBinding {
+        KeyBinding::parse(s).unwrap()
+    }
+
+    #[test]
+    fn shift_letter() {
+        assert_eq!(parse("shift-x"), parse("X"));
+        assert_eq!(parse("SHIFT-x"), parse("X"));
+        assert!(parse("shift-x").matches(&key(KeyCode::Char('X'), KeyModifiers::SHIFT)));
+        // 有的终端大写字母不带shift
+        assert!(parse("X").matches(&key(KeyCode::Char('X'), KeyModifiers::NONE)));
+        assert!(!parse("X").matches(&key(KeyCode::Char('x'), KeyModifiers::NONE)));
+        assert!(!parse("x").matches(&key(KeyCode::Char('X'), KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn shift_tab() {
+        assert_eq!(parse("shift-tab"), parse("backtab"));
+        assert!(parse("shift-tab").matches(&key(KeyCode::BackTab, KeyModifiers::SHIFT)));
+        assert!(parse("shift-tab").matches(&key(KeyCode::BackTab, KeyModifiers::NONE)));
+        assert!(!parse("tab").matches(&key(KeyCode::BackTab, KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn shift_other_keys() {
+        assert!(parse("shift-up").matches(&key(KeyCode::Up, KeyModifiers::SHIFT)));
+        assert!(!parse("shift-up").matches(&key(KeyCode::Up, KeyModifiers::NONE)));
+        assert!(!parse("up").matches(&key(KeyCode::Up, KeyModifiers::SHIFT)));
+    }
+
+    #[test]
+    fn shift_symbol_rejected() {
+        assert_eq!(KeyBinding::parse("shift-1"), None);
+        assert_eq!(KeyBinding::parse("shift-/"), None);
+        assert_eq!(KeyBinding::parse("shift-space"), None);
+    }
+
+    #[test]
+    fn comma() {
+        assert!(parse("comma").matches(&key(KeyCode::Char(','), KeyModifiers::NONE)));
+        assert!(parse("ctrl-comma").matches(&key(KeyCode::Char(','), KeyModifiers::CONTROL)));
+        let keys = parse_keys("comma,x").unwrap();
+        assert_eq!(keys, [parse("comma"), parse("x")]);
+    }
+
+    #[test]
+    fn ctrl_alt() {
+        let ctrl_t = key(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        assert!(parse("ctrl-t").matches(&ctrl_t));
+        assert!(parse("CTRL-t").matches(&ctrl_t));
+        assert!(!parse("t").matches(&ctrl_t));
+        assert!(!parse("ctrl-t").matches(&key(KeyCode::Char('t'), KeyModifiers::NONE)));
+        let ctrl_alt = KeyModifiers::CONTROL | KeyModifiers::ALT;
+        assert!(parse("ctrl-alt-x").matches(&key(KeyCode::Char('x'), ctrl_alt)));
+        assert!(parse("alt-ctrl-x").matches(&key(KeyCode::Char('x'), ctrl_alt)));
+        assert!(parse("alt-shift-x").matches(&key(KeyCode::Char('X'), KeyModifiers::ALT | KeyModifiers::SHIFT)));
+        assert!(parse("ctrl-up").matches(&key(KeyCode::Up, KeyModifiers::CONTROL)));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(KeyBinding::parse("ctrl-"), None);
+        assert_eq!(KeyBinding::parse("foo"), None);
+        assert_eq!(parse_keys("x,foo"), None);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse("ctrl-t").name(), "CTRL-T");
+        assert_eq!(parse("X").name(), "X");
+        assert_eq!(parse("shift-tab").name(), "SHIFT-TAB");
+        assert_eq!(parse("comma").name(), ",");
+    }
+}
@@ -4,70 +4,123 @@ mod g;
 mod my_event;
 mod page;
 mod app_config;
+mod cli;
+mod theme;
+mod keymap;
+mod command;
+mod i18n;
+mod columns;
+mod clipboard;
 
-use crate::clash_api::ProxyData;
-use crate::my_event::AppEvent;
-use crate::page::{ConnectionPage, GroupItemPage, GroupPage, LogPage};
-use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
-use ratatui::layout::{Alignment, Constraint, Direction, Layout};
-use ratatui::style::palette::tailwind;
+use crate::clash_api::{ClashApi, ProxyData};
+use crate::my_event::{AppEvent, CurrentBackend, Mode};
+use crate::page::{operation, BackendPage, ConnectionPage, GroupItemPage, GroupPage, LogPage, Page, PageRegistry};
+use crossterm::event::{self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use crossterm::execute;
+use ratatui::layout::{Alignment, Constraint, Direction, Layout, Position, Rect};
 use ratatui::style::{Style, Stylize};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Paragraph};
+use ratatui::buffer::Buffer;
+use ratatui::widgets::{Block, Paragraph, Widget};
 use ratatui::DefaultTerminal;
 use std::io;
 use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
-use crate::app_config::load_config;
+use crate::app_config::{get_config, load_config, print_config, Backend};
 use crate::page::widget::filter_widget::FilterWidget;
-
-#[derive(PartialEq)]
-enum CurrentPage {
-    Group,
-    GroupItem,
-    Log,
-    Connection,
-}
+use crate::page::widget::column_widget::ColumnWidget;
+use crate::page::widget::detail_widget::DetailWidget;
+use crate::page::widget::help_widget::render_help;
+use crate::page::widget::command_widget::CommandWidget;
+use crate::command::CommandContext;
+use crate::keymap::Action;
+use crate::i18n::{t, tf, Msg};
+use crate::cli::Cli;
+use anyhow::Context;
 
 pub struct App {
-    current_page: CurrentPage,
     app_tx: UnboundedSender<AppEvent>,
     app_rx: UnboundedReceiver<AppEvent>,
     proxy_data: Option<ProxyData>,
     status: String,
 
-    group_page: GroupPage,
-    group_item_page: GroupItemPage,
-    log_page: FilterWidget<LogPage>,
-    connection_page: FilterWidget<ConnectionPage>,
+    pages: PageRegistry,
+    api: ClashApi,
+    // 当前连接的后端，切换后端只修改这里，不修改全局配置
+    backend: Backend,
 
-    menu: Vec<(&'static str, &'static str)>,
+    menu: Vec<(String, &'static str)>,
+    // 上次绘制时菜单中每一项的位置和按键名称，用于鼠标点击
+    menu_areas: Vec<(Rect, String)>,
+    // 上次绘制时标签栏中每个标签的位置
+    tab_areas: Vec<Rect>,
+    // 是否显示帮助
+    show_help: bool,
+    command_widget: CommandWidget,
 }
 
 impl App {
+    // 在所有界面中生效的切换界面的动作
+    const PAGE_ACTIONS: [Action; 4] = [Action::NextPage, Action::PrevPage, Action::GotoPage, Action::Previous];
+
     fn new() -> Self {
         let (app_tx, app_rx) = tokio::sync::mpsc::unbounded_channel();
-        
+        let config = get_config();
+        let api = ClashApi::new(&config.host, &config.key);
+        let backend = Backend {
+            name: config.backend.clone(),
+            host: config.host.clone(),
+            key: config.key.clone(),
+        };
+
+        let status = if config.warnings.is_empty() {
+            t(Msg::Ready).into()
+        } else {
+            config.warnings.join("; ")
+        };
+
+        // 第一个界面为启动后默认显示的界面，新的界面在这里注册
+        let pages: Vec<Box<dyn Page>> = vec![
+            wrap_page(&app_tx, GroupPage::new(app_tx.clone(), api.clone())),
+            wrap_page(&app_tx, GroupItemPage::new(app_tx.clone(), api.clone())),
+            wrap_page(&app_tx, LogPage::new(app_tx.clone(), api.clone())),
+            wrap_page(&app_tx, ConnectionPage::new(app_tx.clone(), api.clone())),
+            wrap_page(&app_tx, BackendPage::new(app_tx.clone(), config.backends, &config.backend)),
+        ];
+
         Self {
-            current_page: CurrentPage::Group,
             proxy_data: Default::default(),
-            status: "就绪".into(),
+            status,
 
-            group_page: GroupPage::new(app_tx.clone()),
-            group_item_page: GroupItemPage::new(app_tx.clone()),
-             log_page: FilterWidget::new(app_tx.clone(), LogPage::new(app_tx.clone())),
-            connection_page: FilterWidget::new(app_tx.clone(), ConnectionPage::new(app_tx.clone())),
+            pages: PageRegistry::new(pages),
+            api,
+            backend,
 
             app_tx,
             app_rx,
 
             menu: vec![],
+            menu_areas: vec![],
+            tab_areas: vec![],
+            show_help: false,
+            command_widget: CommandWidget::default(),
         }
     }
 
     pub async fn run(&mut self) -> anyhow::Result<()> {
-        self.group_page.active().await;
-        self.menu = self.group_page.get_menu();
+        self.pages.current_mut().activate(None);
+        self.menu = self.pages.current().get_menu();
+        let start = get_config().settings.page.id();
+        if start != self.pages.current().id() {
+            self.app_tx.send(AppEvent::ShowPage(start, None))?;
+        }
         let mut terminal = ratatui::init();
+        // ratatui的panic hook只恢复终端，还需要关闭鼠标和粘贴，否则shell中会出现鼠标事件的转义序列
+        let hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            disable_mouse_and_paste();
+            hook(info);
+        }));
+        execute!(io::stdout(), EnableMouseCapture, EnableBracketedPaste)?;
         self.draw(&mut terminal)?;
         loop {
             match self.app_rx.recv().await.unwrap() {
@@ -75,67 +128,152 @@ impl App {
                     return Ok(());
                 }
                 AppEvent::ProxyLoaded(proxy) => {
-                    self.proxy_data = Some(proxy.clone());
-                    match self.current_page {
-                        CurrentPage::Group => self.group_page.on_proxy_loaded(proxy.clone()),
-                        CurrentPage::GroupItem => self.group_item_page.on_proxy_loaded(proxy.clone()),
-                        _ => (),
-                    }
+                    self.pages.broadcast(&proxy);
+                    self.proxy_data = Some(proxy);
                     self.draw(&mut terminal)?;
                 }
                 AppEvent::Key(key_event) => {
-                    match self.current_page {
-                        CurrentPage::Group => self.group_page.on_key(key_event).await,
-                        CurrentPage::GroupItem => self.group_item_page.on_key(key_event).await,
-                        CurrentPage::Log => self.log_page.on_key(key_event).await,
-                        CurrentPage::Connection => self.connection_page.on_key(key_event).await,
-                    };
+                    if self.command_widget.is_active() {
+                        if let Some(line) = self.command_widget.on_key(key_event) {
+                            self.run_command(&line)?;
+                        }
+                        self.draw(&mut terminal)?;
+                        continue;
+                    }
+                    // 显示帮助时，任意键关闭帮助
+                    if self.show_help {
+                        self.show_help = false;
+                        self.draw(&mut terminal)?;
+                        continue;
+                    }
+                    // 编辑过滤条件时字符作为输入，只能用F1等其他按键打开帮助
+                    let typing = self.pages.current().is_editing()
+                        && matches!(key_event.code, KeyCode::Char(_))
+                        && (key_event.modifiers - KeyModifiers::SHIFT).is_empty();
+                    if !typing && keymap::find(&key_event, &[Action::Help]).is_some() {
+                        self.show_help = true;
+                        self.draw(&mut terminal)?;
+                        continue;
+                    }
+                    if !typing && keymap::find(&key_event, &[Action::Command]).is_some() {
+                        let candidates = command::candidates(&self.command_context());
+                        self.command_widget.open(candidates);
+                        self.draw(&mut terminal)?;
+                        continue;
+                    }
+                    if !self.pages.current().is_editing() && self.switch_page(&key_event) {
+                        self.menu = self.pages.current().get_menu();
+                        self.draw(&mut terminal)?;
+                        continue;
+                    }
+                    self.pages.current_mut().on_key(key_event);
                 },
+                AppEvent::Mouse(mouse_event) => {
+                    if self.command_widget.is_active() {
+                        continue;
+                    }
+                    if self.show_help {
+                        if let MouseEventKind::Down(_) = mouse_event.kind {
+                            self.show_help = false;
+                            self.draw(&mut terminal)?;
+                        }
+                        continue;
+                    }
+                    // 点击菜单时相当于按下对应的按键
+                    if let Some(key_event) = self.menu_key(&mouse_event) {
+                        self.app_tx.send(AppEvent::Key(key_event))?;
+                        continue;
+                    }
+                    if let Some(index) = self.tab_index(&mouse_event) {
+                        if self.pages.show_tab(index) {
+                            self.menu = self.pages.current().get_menu();
+                            self.draw(&mut terminal)?;
+                        }
+                        continue;
+                    }
+                    self.pages.current_mut().on_mouse(mouse_event);
+                }
+                // 粘贴到命令面板或者正在编辑的过滤条件
+                AppEvent::Paste(text) => {
+                    if self.command_widget.is_active() {
+                        self.command_widget.paste(&text);
+                        self.draw(&mut terminal)?;
+                    } else if !self.show_help {
+                        self.pages.current_mut().on_paste(&text);
+                    }
+                }
                 AppEvent::Draw => {
                     self.draw(&mut terminal)?;
                 }
-                AppEvent::Status(msg) => {
-                    self.status = msg;
-                    self.menu = match self.current_page {
-                        CurrentPage::Group => self.group_page.get_menu(),
-                        CurrentPage::GroupItem => self.group_page.get_menu(),
-                        CurrentPage::Log => self.log_page.get_menu(),
-                        CurrentPage::Connection => self.connection_page.get_menu(),
+                // 在两次绘制之间写入终端
+                AppEvent::Copy(text, msg) => {
+                    self.status = match clipboard::copy(terminal.backend_mut(), &text) {
+                        Ok(_) => msg,
+                        Err(e) => tf(Msg::CopyError, &[&e]),
                     };
                     self.draw(&mut terminal)?
                 }
-                AppEvent::ShowGroupItemPage(name) => {
-                    self.current_page = CurrentPage::GroupItem;
-                    self.group_item_page.set_group_name(&name);
-                    self.group_item_page
-                        .on_proxy_loaded(self.proxy_data.clone().unwrap());
-                    self.group_item_page.select_selected();
-                    self.menu = self.group_item_page.get_menu();
-                    self.draw(&mut terminal)?;
+                AppEvent::Status(msg) => {
+                    self.status = msg;
+                    self.menu = self.pages.current().get_menu();
+                    self.draw(&mut terminal)?
                 }
-                AppEvent::ShowGroupPage => {
-                    self.current_page = CurrentPage::Group;
-                    self.menu = self.group_page.get_menu();
-                    self.draw(&mut terminal)?;
+                AppEvent::ShowPage(id, param) => {
+                    if self.pages.show(id, param.as_deref()) {
+                        self.menu = self.pages.current().get_menu();
+                    } else {
+                        self.status = tf(Msg::PageNotFound, &[&id]);
+                    }
+                    self.draw(&mut terminal)?
                 }
-                AppEvent::ShowLogPage => {
-                    self.current_page = CurrentPage::Log;
-                    self.log_page.active().await;
-                    self.menu = self.log_page.get_menu();
+                AppEvent::Back => {
+                    if self.pages.back() {
+                        self.menu = self.pages.current().get_menu();
+                    }
                     self.draw(&mut terminal)?
                 }
-                AppEvent::ShowConnection => {
-                    self.current_page = CurrentPage::Connection;
-                    self.connection_page.active().await;
-                    self.menu = self.connection_page.get_menu();
+                AppEvent::SelectBackend(name) => {
+                    let config = get_config();
+                    if let Some(backend) = config.backends.into_iter().find(|x| x.name == name) {
+                        self.set_api(ClashApi::new(&backend.host, &backend.key));
+                        self.pages.broadcast(&CurrentBackend(name.clone()));
+                        self.backend = backend;
+                        self.proxy_data = None;
+                        self.status = tf(Msg::BackendSwitched, &[&name]);
+                    }
+                    self.pages.show(GroupPage::ID, None);
+                    self.menu = self.pages.current().get_menu();
                     self.draw(&mut terminal)?
                 }
+                AppEvent::SetMode(mode) => {
+                    operation::set_mode(self.api.clone(), self.app_tx.clone(), mode);
+                }
+                AppEvent::SelectProxy(group, proxy) => {
+                    operation::select_proxy(self.api.clone(), self.app_tx.clone(), group, proxy);
+                }
+                AppEvent::TestDelay(group) => {
+                    operation::test_delay(self.api.clone(), self.app_tx.clone(), group);
+                }
+                AppEvent::TestProxyDelay(proxies) => {
+                    operation::test_proxies_delay(self.api.clone(), self.app_tx.clone(), proxies);
+                }
+                AppEvent::CloseConnections(filter) => {
+                    operation::close_connections(self.api.clone(), self.app_tx.clone(), filter);
+                }
+                AppEvent::BackendStatus(status) => {
+                    self.pages.broadcast(&status);
+                    self.draw(&mut terminal)?;
+                }
+                AppEvent::ConfigReloaded(config) => {
+                    self.on_config_reloaded(*config);
+                    self.draw(&mut terminal)?;
+                }
                 AppEvent::Log(log) => {
-                    self.log_page.on_data(Box::new(log));
+                    self.pages.broadcast(&log);
                     self.draw(&mut terminal)?;
                 }
                 AppEvent::Connection(connection) => {
-                    self.connection_page.on_data(Box::new(connection));
+                    self.pages.broadcast(&connection);
                     self.draw(&mut terminal)?;
                 }
                 AppEvent::SetMenu(menu) => {
@@ -143,103 +281,303 @@ impl App {
                     self.draw(&mut terminal)?;
                 }
                 AppEvent::ModeChanged(mode) => {
-                    self.group_page.set_current_mode(&mode);
-                    if self.current_page == CurrentPage::Group {
-                        self.menu = self.group_page.get_menu();
-                    }
+                    self.pages.broadcast(&Mode(mode));
+                    self.menu = self.pages.current().get_menu();
                     self.draw(&mut terminal)?;
                 },
             }
         }
     }
 
+    // 当前界面和模式下所有可用的动作；编辑时切换界面、帮助和命令面板的按键不生效或者作为输入，不列出
+    fn actions(&self) -> Vec<Action> {
+        let mut actions = self.pages.current().actions();
+        if !self.pages.current().is_editing() {
+            actions.extend(Self::PAGE_ACTIONS);
+            actions.push(Action::Help);
+            actions.push(Action::Command);
+        }
+        actions
+    }
+
+    fn command_context(&self) -> CommandContext<'_> {
+        CommandContext {
+            proxy: self.proxy_data.as_ref(),
+            backends: get_config().backends.into_iter().map(|x| x.name).collect(),
+        }
+    }
+
+    // 执行命令面板中输入的命令
+    fn run_command(&mut self, line: &str) -> anyhow::Result<()> {
+        match command::parse(line, &self.command_context()) {
+            Ok(event) => self.app_tx.send(event)?,
+            Err(e) => self.status = e,
+        }
+        Ok(())
+    }
+
+    // 处理标签页切换和返回之前界面的按键，已处理时返回true
+    fn switch_page(&mut self, key_event: &event::KeyEvent) -> bool {
+        let count = self.pages.tabs().len();
+        let current = self.pages.current_tab().unwrap_or(0);
+        match keymap::find(key_event, &Self::PAGE_ACTIONS) {
+            Some(Action::NextPage) => {
+                self.pages.show_tab((current + 1) % count);
+            }
+            Some(Action::PrevPage) => {
+                self.pages.show_tab((current + count - 1) % count);
+            }
+            Some(Action::GotoPage) => {
+                if let Some(index) = keymap::find_index(key_event, Action::GotoPage) {
+                    self.pages.show_tab(index);
+                }
+            }
+            Some(_) => {
+                self.pages.back();
+            }
+            None => return false,
+        }
+        true
+    }
+
+    fn tab_index(&self, mouse_event: &MouseEvent) -> Option<usize> {
+        if mouse_event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return None;
+        }
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        self.tab_areas.iter().position(|area| area.contains(position))
+    }
+
+    fn menu_key(&self, mouse_event: &MouseEvent) -> Option<event::KeyEvent> {
+        if mouse_event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return None;
+        }
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        self.menu_areas
+            .iter()
+            .find(|(area, _)| area.contains(position))
+            .and_then(|(_, key)| keymap::key_event(key))
+    }
+
+    fn set_api(&mut self, api: ClashApi) {
+        for page in self.pages.iter_mut() {
+            page.set_api(api.clone());
+        }
+        self.api = api;
+    }
+
+    fn on_config_reloaded(&mut self, config: app_config::Config) {
+        // 保持当前所在的后端，后端的地址或key有变化时重新连接
+        let backend = config.backends.iter()
+            .find(|x| x.name == self.backend.name)
+            .cloned()
+            .unwrap_or_else(|| self.backend.clone());
+        let api_changed = backend.host != self.backend.host || backend.key != self.backend.key;
+        {
+            let mut c = app_config::CONFIG.write().unwrap();
+            *c = config.clone();
+        }
+        theme::set_theme(config.theme);
+        keymap::set_keymap(config.keymap.clone());
+        i18n::set_lang(config.lang);
+
+        self.pages.broadcast(&config);
+        if api_changed {
+            self.set_api(ClashApi::new(&backend.host, &backend.key));
+            self.backend = backend;
+            self.proxy_data = None;
+            self.pages.reactivate();
+        }
+        self.menu = self.pages.current().get_menu();
+
+        self.status = if config.warnings.is_empty() {
+            t(Msg::ConfigReloaded).into()
+        } else {
+            tf(Msg::ConfigReloadedWithWarnings, &[&config.warnings.join("; ")])
+        };
+    }
+
     fn draw(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let theme = theme::theme();
+        let actions = self.actions();
+        let mut menu = self.menu.clone();
+        if !self.pages.current().is_editing() {
+            menu.extend(keymap::menu(&[(Action::Help, t(Msg::Help))]));
+        }
         terminal.draw(|frame| {
             let area = frame.area();
             let layout = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+                .constraints(vec![Constraint::Length(1), Constraint::Fill(1), Constraint::Length(1)])
                 .split(area);
-            match self.current_page {
-                CurrentPage::Group => self.group_page.show(layout[0], frame.buffer_mut()),
-                CurrentPage::GroupItem => self.group_item_page.show(layout[0], frame.buffer_mut()),
-                CurrentPage::Log => self.log_page.show(layout[0], frame.buffer_mut()),
-                CurrentPage::Connection => self.connection_page.show(layout[0], frame.buffer_mut()),
-            }
+            self.tab_areas = render_tabs(&self.pages, layout[0], frame.buffer_mut());
+            self.pages.current_mut().render(layout[1], frame.buffer_mut());
 
             let line = Line::from(vec![Span::raw(self.status.clone())]);
 
             let p_for_msg = Paragraph::new(line)
                 .alignment(Alignment::Left)
-                .fg(tailwind::BLACK)
-                .block(Block::new().bg(tailwind::SLATE.c200));
+                .fg(theme.status_fg)
+                .block(Block::new().bg(theme.status_bg));
 
             let mut line = vec![];
+            // 每一项在菜单中的起始位置和宽度
+            let mut items = vec![];
+            let mut width = 0;
             let count = g::string_width(&self.status);
-            for &(key, text) in &self.menu {
+            for (key, text) in &menu {
                 if !line.is_empty() {
-                    line.push("   ".into())
+                    line.push("   ".into());
+                    width += 3;
                 }
+                let item_width = g::string_width(key) + 1 + g::string_width(text);
+                items.push((width, item_width, key.clone()));
+                width += item_width;
                 line.extend(vec![
-                    Span::styled(key, Style::default().fg(tailwind::RED.c600)),
+                    Span::styled(key.as_str(), Style::default().fg(theme.menu_key_fg)),
                     " ".into(),
-                    Span::styled(text, Style::default().fg(tailwind::BLACK)),
+                    Span::styled(*text, Style::default().fg(theme.menu_fg)),
                 ]);
             }
             let p_for_menu = Paragraph::new(Line::from(line))
                 .alignment(Alignment::Right)
-                .block(Block::new().bg(tailwind::SLATE.c200));
+                .block(Block::new().bg(theme.status_bg));
 
             let layout = Layout::default()
                 .direction(Direction::Horizontal)
                 .constraints(vec![Constraint::Length(count as u16), Constraint::Fill(1)])
-                .split(layout[1]);
+                .split(layout[2]);
             frame.render_widget(p_for_msg, layout[0]);
             frame.render_widget(p_for_menu, layout[1]);
+
+            // 菜单靠右对齐，太长时左边的部分被截掉
+            let menu_area = layout[1];
+            let start = menu_area.right() as i32 - width as i32;
+            self.menu_areas = items
+                .into_iter()
+                .filter_map(|(x, w, key)| {
+                    let left = (start + x as i32).max(menu_area.x as i32);
+                    let right = start + (x + w) as i32;
+                    (right > left).then(|| (Rect::new(left as u16, menu_area.y, (right - left) as u16, 1), key))
+                })
+                .collect();
+
+            if self.show_help {
+                render_help(&actions, area, frame.buffer_mut());
+            }
+            if self.command_widget.is_active() {
+                self.command_widget.render(area, frame.buffer_mut());
+            }
         })?;
         Ok(())
     }
 }
 
+// 在最上面一行显示标签栏，返回每个标签的位置
+fn render_tabs(pages: &PageRegistry, area: Rect, buf: &mut Buffer) -> Vec<Rect> {
+    let theme = theme::theme();
+    Block::new().bg(theme.header_bg).render(area, buf);
+    let keys = keymap::key_names(Action::GotoPage);
+    let current = pages.current_tab();
+    let mut areas = vec![];
+    let mut x = area.x;
+    for (i, page) in pages.tabs().iter().enumerate() {
+        let (bg, fg) = if current == Some(i) {
+            (theme.status_bg, theme.status_fg)
+        } else {
+            (theme.header_bg, theme.header_fg)
+        };
+        let mut spans = vec![Span::raw(" ")];
+        if let Some(key) = keys.get(i) {
+            spans.push(Span::styled(key.clone(), Style::default().fg(theme.menu_key_fg)));
+            spans.push(Span::raw(" "));
+        }
+        spans.push(Span::raw(page.title()));
+        spans.push(Span::raw(" "));
+        let line = Line::from(spans).bg(bg).fg(fg);
+        let width = (line.width() as u16).min(area.right().saturating_sub(x));
+        let tab_area = Rect::new(x, area.y, width, 1);
+        line.render(tab_area, buf);
+        areas.push(tab_area);
+        x += width;
+    }
+    areas
+}
+
+// 所有的界面都用FilterWidget和ColumnWidget包装后注册
+fn wrap_page<T: Page + 'static>(app_tx: &UnboundedSender<AppEvent>, page: T) -> Box<dyn Page> {
+    Box::new(ColumnWidget::new(app_tx.clone(), FilterWidget::new(app_tx.clone(), DetailWidget::new(app_tx.clone(), page))))
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse_localized();
     {
         let mut config = crate::app_config::CONFIG.write().unwrap();
-        *config = load_config().expect("加载配置文件出错");
+        *config = load_config(&cli).context(t(Msg::LoadConfigError))?;
+        theme::set_theme(config.theme);
+        keymap::set_keymap(config.keymap.clone());
+        i18n::set_lang(config.lang);
+        if cli.print_config {
+            for warning in &config.warnings {
+                eprintln!("{warning}");
+            }
+            print!("{}", print_config(&config)?);
+            return Ok(());
+        }
+        let dir = app_config::config_dir(&config);
+        columns::load(dir.as_deref(), &mut config.warnings);
+    }
+    {
+        let config = get_config();
+        ClashApi::new(&config.host, &config.key).get_mode().await.context(t(Msg::ConnectBackendError))?;
     }
-    clash_api::get_mode().await.expect("连接后端出错，请确认配置是否正确");
 
     let mut app = App::new();
 
+    if let Some(path) = get_config().path {
+        let tx = app.app_tx.clone();
+        app_config::watch_config(cli, path, move |config| {
+            let event = match config {
+                Ok(config) => AppEvent::ConfigReloaded(Box::new(config)),
+                Err(e) => AppEvent::Status(tf(Msg::ConfigReloadError, &[&format!("{e:#}")])),
+            };
+            tx.send(event).unwrap_or(());
+        });
+    }
+
     let tx = app.app_tx.clone();
     tokio::spawn(async move {
         loop {
             let e = event::read().expect("failed to read crossterm::event::read");
-            match e {
-                Event::Key(key_event) => {
-                    if key_event.kind == KeyEventKind::Press {
-                        if (key_event.code == KeyCode::Char('c') || key_event.code == KeyCode::Char('C'))
-                            && key_event.modifiers == KeyModifiers::CONTROL
-                        {
-                            tx.send(AppEvent::Quit).unwrap_or(());
-                            break;
-                        }
-                        if tx.send(AppEvent::Key(key_event)).is_err() {
-                            break;
-                        }
-                    }
-                }
-                Event::Resize(_, _) => {
-                    if tx.send(AppEvent::Draw).is_err() {
+            let event = match e {
+                Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                    if (key_event.code == KeyCode::Char('c') || key_event.code == KeyCode::Char('C'))
+                        && key_event.modifiers == KeyModifiers::CONTROL
+                    {
+                        tx.send(AppEvent::Quit).unwrap_or(());
                         break;
                     }
+                    AppEvent::Key(key_event)
                 }
-                _ => {}
+                Event::Mouse(mouse_event) => AppEvent::Mouse(mouse_event),
+                Event::Paste(text) => AppEvent::Paste(text),
+                Event::Resize(_, _) => AppEvent::Draw,
+                _ => continue,
+            };
+            if tx.send(event).is_err() {
+                break;
             }
         }
     });
 
     let app_result = app.run().await;
+    // 先恢复终端，关闭鼠标和粘贴出错时也不影响
     ratatui::restore();
+    disable_mouse_and_paste();
     app_result
 }
+
+fn disable_mouse_and_paste() {
+    execute!(io::stdout(), DisableMouseCapture, DisableBracketedPaste).unwrap_or(());
+}
@@ -1,6 +1,8 @@
 use crate::clash_api::{LogItem, ProxyData};
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, MouseEvent};
 use crate::clash_api::Connection;
+use crate::page::BackendStatus;
+use crate::app_config::Config;
 
 pub enum AppEvent {
     Quit,
@@ -8,14 +10,36 @@ pub enum AppEvent {
     ProxyLoaded(ProxyData),
     ModeChanged(String),
     Key(KeyEvent),
-    SetMenu(Vec<(&'static str, &'static str)>),
+    Mouse(MouseEvent),
+    // 终端的括号粘贴模式下粘贴的文字
+    Paste(String),
+    SetMenu(Vec<(String, &'static str)>),
 
-    ShowGroupPage,
-    ShowGroupItemPage(String),
-    ShowLogPage,
-    ShowConnection,
+    // 界面标识和参数
+    ShowPage(&'static str, Option<String>),
+    // 返回上一个界面
+    Back,
+    SelectBackend(String),
+    SetMode(String),
+    // 分组，节点
+    SelectProxy(String, String),
+    TestDelay(String),
+    // 测试几个节点的延迟
+    TestProxyDelay(Vec<String>),
+    // 关闭满足条件的链接
+    CloseConnections(String),
+    // 通过终端复制到剪贴板的内容，和写入后状态栏显示的文字
+    Copy(String, String),
 
     Status(String),
     Log(LogItem),
     Connection(Connection),
+    BackendStatus(BackendStatus),
+    ConfigReloaded(Box<Config>),
 }
+
+// 代理模式变化后广播给各界面
+pub struct Mode(pub String);
+
+// 切换后端后广播给各界面，内容为后端名称
+pub struct CurrentBackend(pub String);
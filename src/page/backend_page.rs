@@ -0,0 +1,265 @@
+use crate::app_config::{get_config, Backend, Config};
+use crate::clash_api::ClashApi;
+use crate::my_event::{AppEvent, CurrentBackend};
+use crate::i18n::{t, tf, Msg};
+use crate::page::widget::{Click, Query, TableRow, TableWidget, Value};
+use crate::page::{GroupPage, Page};
+use crate::keymap::{self, Action};
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use std::any::Any;
+use std::time::{Duration, Instant};
+use tokio::select;
+use tokio::sync::broadcast;
+use tokio::sync::mpsc::UnboundedSender;
+
+// 单次查询超时，避免一个不可达的后端一直没有结果
+const POLL_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone, Default)]
+pub struct BackendStatus {
+    pub name: String,
+    // None表示在线，否则为连接失败的原因
+    pub error: Option<String>,
+    pub mode: String,
+    // 主分组及其当前节点
+    pub current: String,
+    pub download_speed: u64,
+    pub upload_speed: u64,
+    pub connections: usize,
+}
+
+pub struct BackendPage {
+    table_widget: TableWidget,
+    app_tx: UnboundedSender<AppEvent>,
+    close_tx: Option<broadcast::Sender<()>>,
+    backends: Vec<Backend>,
+    status: Vec<BackendStatus>,
+    // 当前连接的后端名称，第一次进入时选中
+    current: String,
+}
+
+impl BackendPage {
+    pub const ID: &'static str = "backends";
+
+    // 当前界面处理的按键，同时用于生成帮助
+    const ACTIONS: [Action; 3] = [
+        Action::Select,
+        Action::ShowProxy,
+        Action::Quit,
+    ];
+
+    pub fn new(app_tx: UnboundedSender<AppEvent>, backends: Vec<Backend>, current: &str) -> Self {
+        Self {
+            table_widget: TableWidget::new(vec![
+                ("name", Msg::Name),
+                ("address", Msg::Address),
+                ("mode", Msg::Mode),
+                ("current", Msg::CurrentNode),
+                ("down_speed", Msg::DownloadSpeed),
+                ("up_speed", Msg::UploadSpeed),
+                ("connections", Msg::ConnectionCount),
+                ("status", Msg::Status),
+            ]),
+            app_tx,
+            close_tx: None,
+            status: backends.iter().map(|x| BackendStatus { name: x.name.clone(), ..Default::default() }).collect(),
+            backends,
+            current: current.to_string(),
+        }
+    }
+
+    fn set_backends(&mut self, backends: Vec<Backend>) {
+        self.status = backends.iter()
+            .map(|x| {
+                self.status.iter()
+                    .find(|s| s.name == x.name)
+                    .cloned()
+                    .unwrap_or(BackendStatus { name: x.name.clone(), ..Default::default() })
+            })
+            .collect();
+        self.backends = backends;
+        self.update_table();
+    }
+
+    fn on_status(&mut self, status: &BackendStatus) {
+        if let Some(item) = self.status.iter_mut().find(|x| x.name == status.name) {
+            *item = status.clone();
+        }
+        self.update_table();
+    }
+
+    fn update_table(&mut self) {
+        let data = self.backends.iter().zip(&self.status).map(|(backend, status)| {
+            TableRow::new(&backend.name, vec![
+                backend.name.as_str().into(),
+                backend.host.as_str().into(),
+                status.mode.as_str().into(),
+                status.current.as_str().into(),
+                Value::Rate(status.download_speed),
+                Value::Rate(status.upload_speed),
+                Value::Number(status.connections as u64),
+                match &status.error {
+                    None if status.mode.is_empty() => t(Msg::BackendConnecting).into(),
+                    None => t(Msg::BackendOnline).into(),
+                    Some(e) => tf(Msg::BackendOffline, &[e]).into(),
+                },
+            ])
+        }).collect();
+        self.table_widget.set_data(data);
+    }
+}
+
+impl Page for BackendPage {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn title(&self) -> &'static str {
+        t(Msg::Backends)
+    }
+
+    fn activate(&mut self, _param: Option<&str>) {
+        // 第一次进入时选中当前连接的后端，之后保持原来选中的行
+        let first = self.table_widget.current_key().is_none();
+        self.update_table();
+        if first {
+            self.table_widget.select_key(&self.current);
+        }
+
+        let (tx, _) = broadcast::channel::<()>(1);
+        for backend in &self.backends {
+            start_poll_worker(backend.clone(), tx.subscribe(), self.app_tx.clone());
+        }
+        // 重新赋值，则旧的sender会drop，这样所有receiver也会关闭，那么轮询任务就会退出
+        self.close_tx = Some(tx);
+    }
+
+    fn deactivate(&mut self) {
+        self.close_tx = None;
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) {
+        if let Some(event) = self.table_widget.on_key(&key_event) {
+            self.app_tx.send(event).unwrap();
+            return;
+        }
+        match keymap::find(&key_event, &Self::ACTIONS) {
+            Some(Action::Select) => {
+                if let Some(key) = self.table_widget.current_key() {
+                    self.app_tx.send(AppEvent::SelectBackend(key.to_string())).unwrap();
+                }
+            }
+            Some(Action::ShowProxy) => {
+                self.app_tx.send(AppEvent::ShowPage(GroupPage::ID, None)).unwrap();
+            }
+            Some(Action::Quit) => {
+                self.app_tx.send(AppEvent::Quit).unwrap();
+            }
+            _ => {},
+        }
+    }
+
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        match self.table_widget.on_mouse(&mouse_event) {
+            Click::Double(key) => {
+                self.app_tx.send(AppEvent::SelectBackend(key)).unwrap();
+            }
+            Click::None => return,
+            _ => {}
+        }
+        self.app_tx.send(AppEvent::Draw).unwrap();
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.table_widget.render(area, buf)
+    }
+
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::Select, t(Msg::Open)),
+            (Action::Filter, t(Msg::Search)),
+            (Action::ShowProxy, t(Msg::Proxies)),
+            (Action::Quit, t(Msg::Quit)),
+        ])
+    }
+
+    fn actions(&self) -> Vec<Action> {
+        [TableWidget::ACTIONS.as_slice(), &Self::ACTIONS].concat()
+    }
+
+    fn on_data(&mut self, data: &dyn Any) {
+        if let Some(status) = data.downcast_ref::<BackendStatus>() {
+            self.on_status(status);
+        } else if let Some(CurrentBackend(name)) = data.downcast_ref::<CurrentBackend>() {
+            self.current = name.clone();
+        } else if let Some(config) = data.downcast_ref::<Config>() {
+            self.set_backends(config.backends.clone());
+            if self.close_tx.is_some() {
+                self.activate(None);
+            }
+        }
+    }
+
+    // 每个后端使用自己的连接，不受当前后端影响
+    fn set_api(&mut self, _api: ClashApi) {}
+
+    fn set_filter(&mut self, filter: &Query) {
+        self.table_widget.set_filter(filter);
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        Some(&mut self.table_widget)
+    }
+}
+
+// 定时查询一个后端的状态，直到close_rx关闭
+fn start_poll_worker(backend: Backend, mut close_rx: broadcast::Receiver<()>, app_tx: UnboundedSender<AppEvent>) {
+    tokio::spawn(async move {
+        let api = ClashApi::new(&backend.host, &backend.key);
+        let interval = Duration::from_millis(get_config().settings.refresh.backend);
+        let mut last: Option<(Instant, u64, u64)> = None;
+        loop {
+            let status = select! {
+                status = poll(&api, &backend.name, &mut last) => status,
+                _ = close_rx.recv() => break,
+            };
+            if app_tx.send(AppEvent::BackendStatus(status)).is_err() {
+                break;
+            }
+            select! {
+                _ = tokio::time::sleep(interval) => {},
+                _ = close_rx.recv() => break,
+            }
+        }
+    });
+}
+
+async fn poll(api: &ClashApi, name: &str, last: &mut Option<(Instant, u64, u64)>) -> BackendStatus {
+    let mut status = BackendStatus { name: name.to_string(), ..Default::default() };
+    let result = tokio::time::timeout(POLL_TIMEOUT, async {
+        status.mode = api.get_mode().await?;
+        let proxy = api.load_proxy().await?;
+        if let Some(group) = proxy.get_groups().first() {
+            let now = proxy.proxies.get(*group).map(|x| x.now.as_str()).unwrap_or_default();
+            status.current = format!("{group}: {now}");
+        }
+        let connection = api.load_connections().await?;
+        status.connections = connection.connections.len();
+
+        let now = Instant::now();
+        if let Some((time, download_total, upload_total)) = *last {
+            let secs = now.duration_since(time).as_secs_f64().max(1.0);
+            status.download_speed = (connection.download_total.saturating_sub(download_total) as f64 / secs) as u64;
+            status.upload_speed = (connection.upload_total.saturating_sub(upload_total) as f64 / secs) as u64;
+        }
+        *last = Some((now, connection.download_total, connection.upload_total));
+        anyhow::Ok(())
+    }).await.unwrap_or_else(|_| Err(anyhow::anyhow!(t(Msg::Timeout))));
+    if let Err(e) = result {
+        *last = None;
+        status = BackendStatus { name: name.to_string(), error: Some(api.redact(&e.to_string())), ..Default::default() };
+    }
+    status
+}
@@ -1,17 +1,20 @@
-use crate::clash_api::Connection;
+use crate::clash_api::{ClashApi, Connection};
 use crate::clash_api::ConnectionItem;
 use crate::my_event::AppEvent;
-use crate::page::widget::{FilterInnerWidget, TableWidget};
-use crate::page::{start_ws_worker, WsMsg};
-use chrono::{DateTime, Local, TimeDelta, Utc};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::i18n::{t, tf, Msg};
+use crate::g;
+use crate::page::widget::{Click, Query, TableRow, TableWidget, Value};
+use crate::page::widget::detail_widget::DetailSection;
+use crate::page::{start_ws_worker, GroupPage, LogPage, Page, WsMsg};
+use crate::keymap::{self, Action};
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{KeyEvent, MouseEvent};
 use humansize::{format_size, BINARY};
 use indexmap::IndexMap;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use std::any::Any;
 use tokio::sync::mpsc::{channel, Sender, UnboundedSender};
-use crate::app_config::get_config;
 
 pub struct ConnectionPage {
     table_widget: TableWidget,
@@ -22,22 +25,34 @@ pub struct ConnectionPage {
     last_upload_total: u64,
     last_download_total: u64,
     pause: bool,
+    api: ClashApi,
 }
 
 impl ConnectionPage {
-    pub fn new(app_tx: UnboundedSender<AppEvent>) -> Self {
+    pub const ID: &'static str = "connections";
+
+    // 当前界面处理的按键，同时用于生成帮助
+    const ACTIONS: [Action; 5] = [
+        Action::ShowProxy,
+        Action::ShowLog,
+        Action::Pause,
+        Action::CloseConnection,
+        Action::Quit,
+    ];
+
+    pub fn new(app_tx: UnboundedSender<AppEvent>, api: ClashApi) -> Self {
         Self {
             table_widget: TableWidget::new(vec![
-                "源主机",
-                "主机",
-                "链路",
-                "下载速度",
-                "上传速度",
-                "下载量",
-                "上传量",
-                "类型",
-                "连接时间",
-                "规则",
+                ("src", Msg::SourceHost),
+                ("host", Msg::Host),
+                ("chain", Msg::Chain),
+                ("down_speed", Msg::DownloadSpeed),
+                ("up_speed", Msg::UploadSpeed),
+                ("down", Msg::Downloaded),
+                ("up", Msg::Uploaded),
+                ("type", Msg::Type),
+                ("duration", Msg::ConnectedTime),
+                ("rule", Msg::Rule),
             ]),
             app_tx,
             close_tx: None,
@@ -45,70 +60,111 @@ impl ConnectionPage {
             last_upload_total: 0,
             last_download_total: 0,
             pause: false,
+            api,
         }
     }
-    pub async fn inactive(&mut self) {
-        self.app_tx.send(AppEvent::Status("就绪".to_owned())).unwrap();
-        self.close_tx = None;
-        self.table_widget.set_data(vec![]);
-    }
 
 }
 
-impl FilterInnerWidget for ConnectionPage {
-    fn set_filter(&mut self, filter: &str) {
-        self.table_widget.set_filter(filter);
+impl Page for ConnectionPage {
+    fn id(&self) -> &'static str {
+        Self::ID
     }
 
-    fn get_menu(&self) -> Vec<(&'static str, &'static str)> {
-        vec![
-            ("<Space>", if self.pause {"恢复"} else {"暂停"}),
-            ("/", "搜索"),
-            ("P", "代理"),
-            ("L", "日志"),
-            ("ESC", "退出"),
-        ]
+    fn title(&self) -> &'static str {
+        t(Msg::Connections)
     }
 
-    async fn on_key(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Up => {
-                self.table_widget.select_up();
-                self.app_tx.send(AppEvent::Draw).unwrap();
-            }
-            KeyCode::Down => {
-                self.table_widget.select_down();
-                self.app_tx.send(AppEvent::Draw).unwrap();
-            }
-            KeyCode::Char('p') | KeyCode::Char('P') => {
-                self.app_tx.send(AppEvent::ShowGroupPage).unwrap();
-                self.inactive().await;
+    // 保留表格的数据和选中行，返回时继续显示，速度从下一次数据开始重新计算
+    fn deactivate(&mut self) {
+        self.app_tx.send(AppEvent::Status(t(Msg::Ready).to_owned())).unwrap();
+        self.close_tx = None;
+        self.last_data.clear();
+        self.last_upload_total = 0;
+        self.last_download_total = 0;
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) {
+        if let Some(event) = self.table_widget.on_key(&key_event) {
+            self.app_tx.send(event).unwrap();
+            return;
+        }
+        match keymap::find(&key_event, &Self::ACTIONS) {
+            Some(Action::ShowProxy) => {
+                self.app_tx.send(AppEvent::ShowPage(GroupPage::ID, None)).unwrap();
             }
-            KeyCode::Char('l') | KeyCode::Char('L') => {
-                self.app_tx.send(AppEvent::ShowLogPage).unwrap();
-                self.inactive().await;
+            Some(Action::ShowLog) => {
+                self.app_tx.send(AppEvent::ShowPage(LogPage::ID, None)).unwrap();
             }
-            KeyCode::Char(' ') => {
+            Some(Action::Pause) => {
                 self.pause = !self.pause;
-                self.app_tx.send(AppEvent::Status(if self.pause {"暂停"} else {"恢复"}.to_owned())).unwrap();
+                self.app_tx.send(AppEvent::Status(t(if self.pause {Msg::Pause} else {Msg::Resume}).to_owned())).unwrap();
             }
-            KeyCode::Esc => {
+            // 关闭选择的链接，没有选择时关闭当前链接
+            Some(Action::CloseConnection) => {
+                let ids = self.table_widget.selected_keys();
+                if !ids.is_empty() {
+                    self.app_tx.send(AppEvent::CloseConnections(format!("id:{}", ids.join(",")))).unwrap();
+                    self.table_widget.clear_marks();
+                }
+            }
+            Some(Action::Quit) => {
                 self.app_tx.send(AppEvent::Quit).unwrap();
             }
             _ => {},
         }
     }
 
-    fn show(&mut self, area: Rect, buf: &mut Buffer) {
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        if let Click::None = self.table_widget.on_mouse(&mouse_event) {
+            return;
+        }
+        self.app_tx.send(AppEvent::Draw).unwrap();
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
         self.table_widget.render(area, buf);
     }
 
-    async fn active(&mut self) {
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::Pause, t(if self.pause {Msg::Resume} else {Msg::Pause})),
+            (Action::CloseConnection, t(Msg::Close)),
+            (Action::Filter, t(Msg::Search)),
+            (Action::Detail, t(Msg::Detail)),
+            (Action::ShowProxy, t(Msg::Proxies)),
+            (Action::ShowLog, t(Msg::Logs)),
+            (Action::Quit, t(Msg::Quit)),
+        ])
+    }
+
+    fn actions(&self) -> Vec<Action> {
+        [TableWidget::ACTIONS.as_slice(), &Self::ACTIONS].concat()
+    }
+
+    fn set_api(&mut self, api: ClashApi) {
+        self.api = api;
+        self.table_widget.set_data(vec![]);
+    }
+
+    fn set_filter(&mut self, filter: &Query) {
+        self.table_widget.set_filter(filter);
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        Some(&mut self.table_widget)
+    }
+
+    fn activate(&mut self, _param: Option<&str>) {
         self.pause = false;
-        let config = get_config();
-        let url = format!("ws://{}/connections", config.host);
-        let params = [("token", &config.key)];
-        let url = reqwest::Url::parse_with_params(&url, &params).unwrap();
+        let url = match self.api.ws_url("/connections", &[]) {
+            Ok(url) => url,
+            Err(e) => {
+                self.close_tx = None;
+                self.app_tx.send(AppEvent::Status(tf(Msg::LoadDataError, &[&e]))).unwrap();
+                return;
+            }
+        };
 
         let (tx, rx) = channel::<bool>(1);
         // 重新赋值，则旧的sender会drop，这样在receiver也会关闭，那么async{}就会退出循环，并且结束
@@ -117,10 +173,10 @@ impl FilterInnerWidget for ConnectionPage {
         start_ws_worker(url, rx, move |wsmsg| {
             match wsmsg {
                 WsMsg::ConnectFail(s) => {
-                    app_tx.send(AppEvent::Status(format!("连接失败，重试: {s}"))).unwrap();
+                    app_tx.send(AppEvent::Status(tf(Msg::ConnectFailRetry, &[&s]))).unwrap();
                 },
                 WsMsg::Closed => {
-                    app_tx.send(AppEvent::Status("连接已断开，重连".into())).unwrap();
+                    app_tx.send(AppEvent::Status(t(Msg::Disconnected).into())).unwrap();
                 }
                 WsMsg::Message(msg) => {
                     let str = msg.to_text().unwrap();
@@ -129,7 +185,7 @@ impl FilterInnerWidget for ConnectionPage {
                             app_tx.send(AppEvent::Connection(connection)).unwrap();
                         }
                         Err(e) => {
-                            app_tx.send(AppEvent::Status(format!("处理连接数据出错: {e}"))).unwrap();
+                            app_tx.send(AppEvent::Status(tf(Msg::ConnectionDataError, &[&e]))).unwrap();
                         }
                     }
                 }
@@ -137,11 +193,11 @@ impl FilterInnerWidget for ConnectionPage {
         });
     }
 
-    fn on_data(&mut self, data: Box<dyn Any>) {
+    fn on_data(&mut self, data: &dyn Any) {
         if self.pause {
             return;
         }
-        if let Ok(connection) = data.downcast::<Connection>() {
+        if let Some(connection) = data.downcast_ref::<Connection>() {
             let mut status = String::new();
             let mut dlspeed = 0;
             let mut upspeed = 0;
@@ -151,64 +207,16 @@ impl FilterInnerWidget for ConnectionPage {
             if self.last_upload_total > 0 {
                 upspeed = connection.upload_total.saturating_sub(self.last_upload_total);
             }
-            status.push_str(&format!(
-                "下载速度:{}|上传速度:{}|总下载:{}|总上传:{}",
-                format_size(dlspeed, BINARY),
-                format_size(upspeed, BINARY),
-                format_size(connection.download_total, BINARY),
-                format_size(connection.upload_total, BINARY)
-            ));
+            status.push_str(&tf(Msg::Traffic, &[
+                &format_size(dlspeed, BINARY),
+                &format_size(upspeed, BINARY),
+                &format_size(connection.download_total, BINARY),
+                &format_size(connection.upload_total, BINARY),
+            ]));
             self.last_download_total = connection.download_total;
             self.last_upload_total = connection.upload_total;
 
             self.app_tx.send(AppEvent::Status(status)).unwrap();
-            fn format_duration(duration: TimeDelta) -> String {
-                let seconds = duration.num_seconds();
-                if seconds < 10 {
-                    return "几秒前".to_string();
-                }
-                let minutes = seconds / 60;
-                let hours = minutes / 60;
-                let days = hours / 24;
-                let remaining_seconds = seconds % 60;
-                let remaining_minutes = minutes % 60;
-                let remaining_hours = hours % 24;
-
-                let mut result = String::new();
-                let mut v = vec![Some(days), Some(remaining_hours), Some(remaining_minutes), Some(remaining_seconds)];
-                for i in &mut v {
-                    if let Some(n) = i {
-                        match *n {
-                            0 => *i = None,
-                            _ => break,
-                        };
-                    }
-                }
-                let mut count = 0;
-                for i in &mut v {
-                    if i.is_some() {
-                        count += 1;
-                        if count > 2 {
-                            *i = None
-                        }
-                    }
-                }
-                for i in &mut v.iter_mut().rev() {
-                    if let Some(n) = i {
-                        match *n {
-                            0 => *i = None,
-                            _ => break,
-                        }
-                    }
-                }
-                for (idx, item) in v.iter().enumerate() {
-                    if let Some(n) = item {
-                        result.push_str(&format!("{n}{}", ["天", "小时", "分", "秒"][idx]));
-                    }
-                }
-                result
-            }
-
             fn get_not_empty<'a>(v: &[&'a str]) -> &'a str {
                 for i in v {
                     if !i.is_empty() {
@@ -224,45 +232,63 @@ impl FilterInnerWidget for ConnectionPage {
                     Ok(utc_datetime) => {
                         let local_datetime: DateTime<Local> = utc_datetime.with_timezone(&Local);
                         let duration = now.signed_duration_since(local_datetime);
-                        format_duration(duration)
+                        Value::Duration(duration.num_seconds().max(0) as u64)
                     },
                     Err(e) => {
-                        self.app_tx.send(AppEvent::Status(format!("格式化时间错误: {} {e}", conn.start))).unwrap();
-                        String::new()
+                        self.app_tx.send(AppEvent::Status(tf(Msg::TimeFormatError, &[&conn.start, &e]))).unwrap();
+                        Value::Empty
                     },
                 };
                 let last_connection = self.last_data.get(&conn.id);
-                let dlspeed = match last_connection {
-                    Some(last_connection) => { format_size(conn.download.saturating_sub(last_connection.download), BINARY) }
-                    None => {format_size(0usize, BINARY)}
-                };
-                let upspeed = match last_connection {
-                    Some(last_connection) => { format_size(conn.upload.saturating_sub(last_connection.upload), BINARY) }
-                    None => {format_size(0usize, BINARY)}
-                };
+                let dlspeed = last_connection.map_or(0, |x| conn.download.saturating_sub(x.download));
+                let upspeed = last_connection.map_or(0, |x| conn.upload.saturating_sub(x.upload));
 
-                data.push(vec![
-                    conn.metadata.source_ip.clone(),
-                    format!("{}:{}", get_not_empty(&[conn.metadata.sniff_host.as_str(), conn.metadata.host.as_str(), conn.metadata.destination_ip.as_str()]), conn.metadata.destination_port),
-                    conn.chains.last().unwrap_or(&"".to_string()).clone(),
-                    dlspeed,
-                    upspeed,
-                    format_size(conn.download, BINARY),
-                    format_size(conn.upload, BINARY),
-                    format!("{}({})", conn.metadata.inbound_name, conn.metadata.network),
+                data.push(TableRow::new(&conn.id, vec![
+                    conn.metadata.source_ip.as_str().into(),
+                    format!("{}:{}", get_not_empty(&[conn.metadata.sniff_host.as_str(), conn.metadata.host.as_str(), conn.metadata.destination_ip.as_str()]), conn.metadata.destination_port).into(),
+                    conn.chains.last().map_or("", String::as_str).into(),
+                    Value::Rate(dlspeed),
+                    Value::Rate(upspeed),
+                    Value::Bytes(conn.download),
+                    Value::Bytes(conn.upload),
+                    format!("{}({})", conn.metadata.inbound_name, conn.metadata.network).into(),
                     dt_start,
-                    if conn.rule_payload.is_empty() { conn.rule.clone() } else { conn.rule_payload.clone() },
-                ])
+                    if conn.rule_payload.is_empty() { conn.rule.as_str() } else { conn.rule_payload.as_str() }.into(),
+                ]))
             }
             self.last_data.clear();
-            for v in connection.connections {
-                self.last_data.insert(v.id.clone(), v);
+            for v in &connection.connections {
+                self.last_data.insert(v.id.clone(), v.clone());
             }
             data.sort_by(|a, b| {
-                a[1].cmp(&b[1])
+                a.cells[1].value.compare(&b.cells[1].value, false)
             });
             self.table_widget.set_data(data);
         }
     }
 
+
+    // 链路按从入站到出站的顺序显示
+    fn detail(&self) -> Option<Vec<DetailSection>> {
+        let Some(conn) = self.table_widget.current_key().and_then(|x| self.last_data.get(x)) else {
+            return Some(vec![]);
+        };
+        let metadata = &conn.metadata;
+        let chain = conn.chains.iter().rev().map(String::as_str).collect::<Vec<_>>().join(" → ");
+        let section = DetailSection::new(t(Msg::DetailGeneral))
+            .item(t(Msg::DetailId), &conn.id)
+            .item(t(Msg::Host), &metadata.host)
+            .item(t(Msg::DetailSniffHost), &metadata.sniff_host)
+            .item(t(Msg::DetailDestination), format!("{}:{}", metadata.destination_ip, metadata.destination_port))
+            .item(t(Msg::SourceHost), format!("{}:{}", metadata.source_ip, metadata.source_port))
+            .item(t(Msg::DetailInbound), &metadata.inbound_name)
+            .item(t(Msg::DetailNetwork), &metadata.network)
+            .item(t(Msg::Chain), chain)
+            .item(t(Msg::Rule), &conn.rule)
+            .item(t(Msg::DetailRulePayload), &conn.rule_payload)
+            .item(t(Msg::DetailStart), g::local_time(&conn.start))
+            .item(t(Msg::Downloaded), format_size(conn.download, BINARY))
+            .item(t(Msg::Uploaded), format_size(conn.upload, BINARY));
+        Some(vec![section])
+    }
 }
@@ -1,133 +1,199 @@
-use crate::clash_api;
-use crate::clash_api::ProxyData;
+use crate::clash_api::{ClashApi, ProxyData, ProxyItem};
 use crate::my_event::AppEvent;
-use crate::my_event::AppEvent::{ProxyLoaded, ShowGroupPage, Status};
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::i18n::{t, Msg};
+use crate::my_event::AppEvent::{Back, ShowPage};
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use std::any::Any;
 use tokio::sync::mpsc::UnboundedSender;
-use crate::page::widget::TableWidget;
+use crate::page::widget::{CellStyle, Click, Query, TableRow, TableWidget, Value};
+use crate::page::{operation, GroupPage, LogPage, Page};
+use crate::page::group_page::proxy_detail;
+use crate::page::widget::detail_widget::DetailSection;
+use crate::keymap::{self, Action};
 
 pub struct GroupItemPage {
     table_widget: TableWidget,
     group_name: String,
+    // 最后一次加载的代理数据，切换分组时使用
+    proxy: Option<ProxyData>,
     app_tx: UnboundedSender<AppEvent>,
+    api: ClashApi,
 }
 
 impl GroupItemPage {
-    pub fn new(app_tx: UnboundedSender<AppEvent>) -> Self {
-        let mut table_widget = TableWidget::new(vec!["名称", "延迟", "选中"]);
+    pub const ID: &'static str = "group";
+
+    // 当前界面处理的按键，同时用于生成帮助
+    const ACTIONS: [Action; 4] = [
+        Action::TestDelay,
+        Action::ShowLog,
+        Action::Select,
+        Action::Back,
+    ];
+
+    pub fn new(app_tx: UnboundedSender<AppEvent>, api: ClashApi) -> Self {
+        let mut table_widget = TableWidget::new(vec![("name", Msg::Name), ("delay", Msg::Delay), ("current", Msg::Current)]);
         table_widget.set_data(vec![]);
         Self {
             table_widget,
             app_tx,
+            api,
+            proxy: None,
             group_name: String::default(),
         }
     }
 
-    pub fn get_menu(&self) -> Vec<(&'static str, &'static str)> {
-        vec![
-            ("CTRL-T", "测速"),
-            ("ENTER", "选择"),
-            ("ESC", "返回"),
-        ]
+    // 分组当前的节点
+    fn current_proxy(&self) -> Option<&str> {
+        self.proxy
+            .as_ref()
+            .and_then(|x| x.proxies.get(&self.group_name))
+            .map(|x| x.now.as_str())
     }
 
-    pub async fn on_key(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Up => {
-                self.select_up();
-                self.app_tx.send(AppEvent::Draw).unwrap();
+    fn update_table(&mut self) {
+        let data = self.proxy
+            .as_ref()
+            .and_then(|x| x.proxies.get(&self.group_name).map(|group| group_rows(x, group)))
+            .unwrap_or_default();
+        self.table_widget.set_data(data);
+    }
+
+    // 将选中的节点设为分组的当前节点，然后返回分组界面
+    fn select_current(&self) {
+        if let Some(key) = self.table_widget.current_key() {
+            self.app_tx.send(Back).unwrap();
+            if self.current_proxy() != Some(key) {
+                self.app_tx.send(AppEvent::SelectProxy(self.group_name.clone(), key.to_string())).unwrap();
             }
-            KeyCode::Down => {
-                self.select_down();
-                self.app_tx.send(AppEvent::Draw).unwrap();
+        }
+    }
+}
+
+impl Page for GroupItemPage {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn title(&self) -> &'static str {
+        t(Msg::Group)
+    }
+
+    fn parent(&self) -> Option<&'static str> {
+        Some(GroupPage::ID)
+    }
+
+    fn activate(&mut self, param: Option<&str>) {
+        if let Some(group_name) = param {
+            // 不同分组中有同名的节点，切换分组时取消选择，以免测速时只测试上一个分组中选择的节点
+            if self.group_name != group_name {
+                self.table_widget.clear_marks();
             }
-            KeyCode::Char('t') | KeyCode::Char('T') => {
-                if key_event.modifiers == KeyModifiers::CONTROL {
-                    let group_name = self.group_name.clone();
-                    let tx = self.app_tx.clone();
-                    tokio::spawn(async move {
-                        tx.send(Status("测速中...".into())).unwrap();
-                        match clash_api::check_delay(&group_name).await {
-                            Ok(_) => {}
-                            Err(e) => {tx.send(Status(format!("检查延时出错: {e}"))).unwrap();}
-                        }
-                        let proxy = clash_api::load_proxy().await;
-                        match proxy {
-                            Ok(proxy) => {
-                                tx.send(ProxyLoaded(proxy)).unwrap();
-                                tx.send(Status("测速完成".into())).unwrap();
-                            },
-                            Err(e) => {
-                                tx.send(Status(format!("加载数据出错: {e}"))).unwrap();
-                            }
-                        }
-                    });
+            self.group_name = group_name.into();
+            self.update_table();
+            let now = self.current_proxy().unwrap_or_default().to_string();
+            self.table_widget.select_key(&now);
+        }
+        let app_tx = self.app_tx.clone();
+        let api = self.api.clone();
+        tokio::spawn(async move {
+            operation::load_proxy(&api, &app_tx).await;
+        });
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) {
+        if let Some(event) = self.table_widget.on_key(&key_event) {
+            self.app_tx.send(event).unwrap();
+            return;
+        }
+        match keymap::find(&key_event, &Self::ACTIONS) {
+            // 选择了节点时只测试选择的节点
+            Some(Action::TestDelay) => {
+                let proxies = self.table_widget.marked_keys();
+                match proxies.is_empty() {
+                    true => self.app_tx.send(AppEvent::TestDelay(self.group_name.clone())).unwrap(),
+                    false => self.app_tx.send(AppEvent::TestProxyDelay(proxies)).unwrap(),
                 }
             }
-            KeyCode::Char('l') | KeyCode::Char('L') => {
-                self.app_tx.send(AppEvent::ShowLogPage).unwrap();
-            }
-            KeyCode::Enter => {
-                if let Some(row) = self.table_widget.current_row() {
-                    if row[2].is_empty() {
-                        self.app_tx
-                            .send(ShowGroupPage)
-                            .unwrap();
-                        let new_group = row[0].clone();
-
-                        let group_name = self.group_name.clone();
-                        let tx = self.app_tx.clone();
-                        tokio::spawn(async move {
-                            if let Err(err) = clash_api::select_group_current(&group_name, &new_group).await {
-                                tx.send(Status(format!("设置分组出错: {err}"))).unwrap();
-                            }
-                            let proxy = clash_api::load_proxy().await;
-                            match proxy {
-                                Ok(proxy) => {
-                                    tx.send(ProxyLoaded(proxy)).unwrap();
-                                },
-                                Err(e) => {
-                                    tx.send(Status(format!("加载数据出错: {e}"))).unwrap();
-                                }
-                            }
-                        });
-                    } else {
-                        self.app_tx.send(ShowGroupPage).unwrap();
-                    }
-                }
+            Some(Action::ShowLog) => {
+                self.app_tx.send(ShowPage(LogPage::ID, None)).unwrap();
             }
-            KeyCode::Esc => {
-                if self.table_widget.current_row().is_some() {
-                    self.app_tx.send(ShowGroupPage).unwrap();
-                }
+            Some(Action::Select) => self.select_current(),
+            Some(Action::Back) => {
+                self.app_tx.send(Back).unwrap();
             }
             _ => {},
         }
     }
 
-    pub fn set_group_name(&mut self, group_name: &str) {
-        self.group_name = group_name.into();
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        match self.table_widget.on_mouse(&mouse_event) {
+            Click::Double(_) => self.select_current(),
+            Click::None => return,
+            _ => {}
+        }
+        self.app_tx.send(AppEvent::Draw).unwrap();
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.table_widget.render(area, buf)
+    }
+
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::TestDelay, t(Msg::TestDelay)),
+            (Action::Filter, t(Msg::Search)),
+            (Action::ShowLog, t(Msg::Logs)),
+            (Action::Select, t(Msg::Select)),
+            (Action::Back, t(Msg::Back)),
+        ])
+    }
+
+    fn actions(&self) -> Vec<Action> {
+        [TableWidget::ACTIONS.as_slice(), &Self::ACTIONS].concat()
     }
 
-    pub fn on_proxy_loaded(&mut self, proxy: ProxyData) {
-        self.table_widget
-            .set_data(proxy.to_group_items(&self.group_name))
+    fn on_data(&mut self, data: &dyn Any) {
+        if let Some(proxy) = data.downcast_ref::<ProxyData>() {
+            self.proxy = Some(proxy.clone());
+            self.update_table();
+        }
     }
 
-    pub fn select_up(&mut self) {
-        self.table_widget.select_up();
+    fn set_api(&mut self, api: ClashApi) {
+        self.api = api;
+        self.proxy = None;
     }
-    pub fn select_down(&mut self) {
-        self.table_widget.select_down();
+
+    fn set_filter(&mut self, filter: &Query) {
+        self.table_widget.set_filter(filter);
     }
 
-    pub fn select_selected(&mut self) {
-        self.table_widget.select(|x| !x[2].is_empty())
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        Some(&mut self.table_widget)
     }
 
-    pub fn show(&mut self, area: Rect, buffer: &mut Buffer) {
-        self.table_widget.render(area, buffer)
+    fn detail(&self) -> Option<Vec<DetailSection>> {
+        let proxy = self.proxy.as_ref()?;
+        let item = self.table_widget.current_key().and_then(|x| proxy.proxies.get(x));
+        Some(item.map_or(vec![], |x| proxy_detail(proxy, x)))
     }
 }
+
+// 分组中的节点，当前节点用checked_fg显示
+fn group_rows(proxy: &ProxyData, group: &ProxyItem) -> Vec<TableRow> {
+    group.all
+        .iter()
+        .map(|name| {
+            let delay = proxy.proxies.get(name).and_then(|x| x.get_delay(&proxy.proxies));
+            let checked = group.now == *name;
+            let row = TableRow::new(name, vec![name.as_str().into(), Value::delay(delay), Value::Flag(checked)]);
+            match checked {
+                true => row.with_style(CellStyle::Checked),
+                false => row,
+            }
+        })
+        .collect()
+}
@@ -1,43 +1,69 @@
-use crate::clash_api;
-use crate::clash_api::ProxyData;
-use crate::my_event::AppEvent;
-use crate::my_event::AppEvent::{ProxyLoaded, ShowGroupItemPage, Status};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::clash_api::{ClashApi, ProxyData, ProxyItem};
+use crate::my_event::{AppEvent, Mode};
+use crate::i18n::{t, tf, Msg};
+use crate::my_event::AppEvent::{ModeChanged, ShowPage, Status};
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
+use std::any::Any;
 use tokio::sync::mpsc::UnboundedSender;
-use crate::page::widget::TableWidget;
+use crate::g;
+use crate::page::widget::{Click, Query, TableRow, TableWidget, Value};
+use crate::page::widget::detail_widget::DetailSection;
+use crate::page::{operation, BackendPage, ConnectionPage, GroupItemPage, LogPage, Page};
+use crate::keymap::{self, Action};
 
-const MODE_RULE: &str = "模式:RULE";
-const MODE_GLOBAL: &str = "模式:GLOBAL";
-const MODE_DIRECT: &str = "模式:DIRECT";
+const MODE_RULE: Msg = Msg::ModeRule;
+const MODE_GLOBAL: Msg = Msg::ModeGlobal;
+const MODE_DIRECT: Msg = Msg::ModeDirect;
 
 pub struct GroupPage {
-    current_mode: &'static str,
+    current_mode: Msg,
     table_widget: TableWidget,
-    selected: String,
+    // 最后一次加载的代理数据，用于显示详情
+    proxy: Option<ProxyData>,
     app_tx: UnboundedSender<AppEvent>,
+    api: ClashApi,
 }
 
 impl GroupPage {
-    pub fn new(app_tx: UnboundedSender<AppEvent>) -> Self {
-        let mut table_widget = TableWidget::new(vec!["名称", "详情", "延迟"]);
+    pub const ID: &'static str = "proxy";
+
+    // 当前界面处理的按键，同时用于生成帮助
+    const ACTIONS: [Action; 6] = [
+        Action::ShowLog,
+        Action::ShowConnection,
+        Action::ShowBackend,
+        Action::Quit,
+        Action::Select,
+        Action::SwitchMode,
+    ];
+
+    pub fn new(app_tx: UnboundedSender<AppEvent>, api: ClashApi) -> Self {
+        let mut table_widget = TableWidget::new(vec![("name", Msg::Name), ("current", Msg::Detail), ("delay", Msg::Delay)]);
         table_widget.set_data(vec![]);
         Self {
             current_mode: MODE_RULE,
             table_widget,
+            proxy: None,
             app_tx,
-
-            selected: String::new(),
+            api,
         }
     }
 
-    pub fn on_proxy_loaded(&mut self, proxy: ProxyData) {
-        self.table_widget.set_data(proxy.to_groups());
-        self.table_widget.select(|x| x[0].eq(&self.selected));
+    fn on_proxy_loaded(&mut self, proxy: &ProxyData) {
+        let data = proxy.get_groups()
+            .into_iter()
+            .filter_map(|name| proxy.proxies.get(name))
+            .map(|group| {
+                let delay = group.get_delay(&proxy.proxies);
+                TableRow::new(&group.name, vec![group.name.as_str().into(), group.now.as_str().into(), Value::delay(delay)])
+            })
+            .collect();
+        self.table_widget.set_data(data);
     }
 
-    pub fn set_current_mode(&mut self, mode: &str) {
+    fn set_current_mode(&mut self, mode: &str) {
         self.current_mode = match mode.to_lowercase().as_str() {
             "direct" => MODE_DIRECT,
             "global" => MODE_GLOBAL,
@@ -45,101 +71,157 @@ impl GroupPage {
             _ => MODE_RULE,
         }
     }
+}
 
-    pub fn get_menu(&self) -> Vec<(&'static str, &'static str)> {
-        vec![
-            ("M", self.current_mode),
-            ("L", "日志"),
-            ("C", "链接"),
-            ("ENTER", "查看"),
-            ("ESC", "退出"),
-        ]
-    }
-    pub async fn on_key(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Up => {
-                self.select_up();
-                self.app_tx.send(AppEvent::Draw).unwrap();
-            }
-            KeyCode::Down => {
-                self.select_down();
-                self.app_tx.send(AppEvent::Draw).unwrap();
+impl Page for GroupPage {
+    fn id(&self) -> &'static str {
+        Self::ID
+    }
+
+    fn title(&self) -> &'static str {
+        t(Msg::Proxies)
+    }
+
+    fn activate(&mut self, _param: Option<&str>) {
+        let app_tx = self.app_tx.clone();
+        let api = self.api.clone();
+        tokio::spawn(async move {
+            match api.get_mode().await {
+                Ok(mode) => {
+                    app_tx.send(ModeChanged(mode)).unwrap();
+                }
+                Err(e) => {
+                    app_tx.send(Status(tf(Msg::LoadDataError, &[&e]))).unwrap();
+                },
+            };
+            operation::load_proxy(&api, &app_tx).await;
+        });
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) {
+        if let Some(event) = self.table_widget.on_key(&key_event) {
+            self.app_tx.send(event).unwrap();
+            return;
+        }
+        match keymap::find(&key_event, &Self::ACTIONS) {
+            Some(Action::ShowLog) => {
+                self.app_tx.send(ShowPage(LogPage::ID, None)).unwrap();
             }
-            KeyCode::Char('l') | KeyCode::Char('L') => {
-                self.app_tx.send(AppEvent::ShowLogPage).unwrap();
+            Some(Action::ShowConnection) => {
+                self.app_tx.send(ShowPage(ConnectionPage::ID, None)).unwrap();
             }
-            KeyCode::Char('c') | KeyCode::Char('C') => {
-                self.app_tx.send(AppEvent::ShowConnection).unwrap();
+            Some(Action::ShowBackend) => {
+                self.app_tx.send(ShowPage(BackendPage::ID, None)).unwrap();
             }
-            KeyCode::Esc => {
+            Some(Action::Quit) => {
                 self.app_tx.send(AppEvent::Quit).unwrap();
             }
-            KeyCode::Enter => {
-                if let Some(row) = self.table_widget.current_row() {
-                    self.app_tx.send(ShowGroupItemPage(row[0].clone())).unwrap();
+            Some(Action::Select) => {
+                if let Some(key) = self.table_widget.current_key() {
+                    self.app_tx.send(ShowPage(GroupItemPage::ID, Some(key.to_string()))).unwrap();
                 }
             }
-            KeyCode::Char('m') | KeyCode::Char('M')  => {
+            Some(Action::SwitchMode) => {
                 let new_mode = match self.current_mode {
                     MODE_DIRECT => "rule",
                     MODE_RULE => "global",
                     MODE_GLOBAL => "direct",
                     _ => "rule",
                 };
-                let app_tx = self.app_tx.clone();
-                if let Err(err) = clash_api::set_mode(new_mode).await {
-                    app_tx.send(Status(format!("设置模式出错: {err}"))).unwrap();
-                } else {
-                    match clash_api::get_mode().await {
-                        Ok(mode) => {
-                            app_tx.send(AppEvent::ModeChanged(mode)).unwrap();
-                        },
-                        Err(e) => {
-                            app_tx.send(Status(format!("加载数据出错: {e}"))).unwrap();
-                        },
-                    };
-                }
+                self.app_tx.send(AppEvent::SetMode(new_mode.into())).unwrap();
             }
             _ => {},
         }
     }
-    pub fn select_up(&mut self) {
-        let row = self.table_widget.select_up();
-        if !row.is_empty() {
-            self.selected = row[0].clone();
+
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        match self.table_widget.on_mouse(&mouse_event) {
+            Click::Double(key) => {
+                self.app_tx.send(ShowPage(GroupItemPage::ID, Some(key))).unwrap();
+            }
+            Click::None => return,
+            _ => {}
         }
+        self.app_tx.send(AppEvent::Draw).unwrap();
     }
-    pub fn select_down(&mut self) {
-        let row = self.table_widget.select_down();
-        if !row.is_empty() {
-            self.selected = row[0].clone();
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.table_widget.render(area, buf)
+    }
+
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::SwitchMode, t(self.current_mode)),
+            (Action::Filter, t(Msg::Search)),
+            (Action::ShowLog, t(Msg::Logs)),
+            (Action::ShowConnection, t(Msg::Connections)),
+            (Action::ShowBackend, t(Msg::Backends)),
+            (Action::Select, t(Msg::View)),
+            (Action::Quit, t(Msg::Quit)),
+        ])
+    }
+
+    fn actions(&self) -> Vec<Action> {
+        [TableWidget::ACTIONS.as_slice(), &Self::ACTIONS].concat()
+    }
+
+    fn on_data(&mut self, data: &dyn Any) {
+        if let Some(proxy) = data.downcast_ref::<ProxyData>() {
+            self.on_proxy_loaded(proxy);
+            self.proxy = Some(proxy.clone());
+        } else if let Some(Mode(mode)) = data.downcast_ref::<Mode>() {
+            self.set_current_mode(mode);
         }
     }
 
-    pub fn show(&mut self, area: Rect, buffer: &mut Buffer) {
-        self.table_widget.render(area, buffer)
+    fn set_api(&mut self, api: ClashApi) {
+        self.api = api;
+        self.proxy = None;
+        self.table_widget.set_data(vec![]);
     }
 
-    pub async fn active(&mut self) {
-        let app_tx = self.app_tx.clone();
-        tokio::spawn(async move {
-            match clash_api::get_mode().await {
-                Ok(mode) => {
-                    app_tx.send(AppEvent::ModeChanged(mode)).unwrap();
-                }
-                Err(e) => {
-                    app_tx.send(Status(format!("加载数据出错: {e}"))).unwrap();
-                },
-            };
-            let proxy = clash_api::load_proxy().await;
-            match proxy {
-                Ok(proxy) => {
-                    app_tx.send(ProxyLoaded(proxy)).unwrap();
-                },
-                Err(e) => {
-                    app_tx.send(Status(format!("加载数据出错: {e}"))).unwrap();
-                }
-            }
-        });
+    fn set_filter(&mut self, filter: &Query) {
+        self.table_widget.set_filter(filter);
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        Some(&mut self.table_widget)
+    }
+
+    fn detail(&self) -> Option<Vec<DetailSection>> {
+        let proxy = self.proxy.as_ref()?;
+        let item = self.table_widget.current_key().and_then(|x| proxy.proxies.get(x));
+        Some(item.map_or(vec![], |x| proxy_detail(proxy, x)))
+    }
+}
+
+// 分组或节点的详情：基本信息、分组中的节点和测速记录，没有内容的部分不显示
+pub(super) fn proxy_detail(proxy: &ProxyData, item: &ProxyItem) -> Vec<DetailSection> {
+    let delay = |x: &ProxyItem| Value::delay(x.get_delay(&proxy.proxies)).text();
+    let mut general = DetailSection::new(t(Msg::DetailGeneral))
+        .item(t(Msg::Name), &item.name)
+        .item(t(Msg::Type), &item.r#type);
+    if !item.now.is_empty() {
+        general = general.item(t(Msg::CurrentNode), &item.now);
+    }
+    let mut sections = vec![general.item(t(Msg::Delay), delay(item))];
+    if !item.all.is_empty() {
+        let members = item.all
+            .iter()
+            .fold(DetailSection::new(tf(Msg::DetailMembers, &[&item.all.len()])), |section, name| {
+                let value = proxy.proxies.get(name).map(delay).unwrap_or_default();
+                section.item(name, value)
+            });
+        sections.push(members);
+    }
+    if !item.history.is_empty() {
+        let history = item.history
+            .iter()
+            .rev()
+            .fold(DetailSection::new(t(Msg::DetailHistory)), |section, x| {
+                section.item(g::local_time(&x.time), Value::delay(Some(x.delay)).text())
+            });
+        sections.push(history);
     }
+    sections
 }
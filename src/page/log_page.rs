@@ -1,92 +1,79 @@
-use crate::clash_api::LogItem;
+use crate::clash_api::{ClashApi, LogItem};
 use crate::my_event::AppEvent;
-use crate::page::widget::{FilterInnerWidget, LogWidget};
-use crate::page::{start_ws_worker, WsMsg};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::i18n::{t, tf, Msg};
+use crate::page::widget::{LogWidget, Query, Scroll};
+use crate::page::widget::detail_widget::DetailSection;
+use crate::page::{start_ws_worker, ConnectionPage, GroupPage, Page, WsMsg};
+use crate::keymap::{self, Action};
+use crate::app_config::{get_config, Config};
+use crate::cli::LogLevel;
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use std::any::Any;
+use std::sync::LazyLock;
+use regex::Regex;
 use tokio::sync::mpsc::{channel, Sender, UnboundedSender};
-use crate::app_config::get_config;
+
+// 链接的日志，比如"[TCP] 127.0.0.1:5000 --> example.com:443 match DomainSuffix(example.com) using Proxy[节点]"
+static CONNECTION_LOG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[(\w+)\] (.+?) --> (\S+) (?:match (.+?) )?.*?using (.+)$").unwrap()
+});
 
 pub struct LogPage {
     log_widget: LogWidget,
     app_tx: UnboundedSender<AppEvent>,
     close_tx: Option<Sender<bool>>,
+    // 订阅时使用的日志级别，配置修改后重新订阅
+    log_level: LogLevel,
     pause: bool,
+    api: ClashApi,
 }
 
 impl LogPage {
-    pub fn new(app_tx: UnboundedSender<AppEvent>) -> Self {
+    pub const ID: &'static str = "logs";
+
+    // 当前界面处理的按键，同时用于生成帮助
+    const ACTIONS: [Action; 4] = [
+        Action::ShowProxy,
+        Action::ShowConnection,
+        Action::Pause,
+        Action::Quit,
+    ];
+
+    pub fn new(app_tx: UnboundedSender<AppEvent>, api: ClashApi) -> Self {
+        let config = get_config();
         Self {
-            log_widget: LogWidget::new(5000),
+            log_widget: LogWidget::new(config.settings.log.buffer_size),
             app_tx,
             close_tx: None,
+            log_level: config.settings.log_level,
             pause: false,
+            api,
         }
     }
-
-    pub async fn inactive(&mut self) {
-        self.close_tx = None;
-        self.log_widget.clear();
-    }
-
 }
 
-impl FilterInnerWidget for LogPage {
-    fn set_filter(&mut self, filter: &str) {
-        self.log_widget.set_filter(filter);
-    }
-
-    fn get_menu(&self) -> Vec<(&'static str, &'static str)> {
-        vec![
-            ("<Space>", if self.pause {"恢复"} else {"暂停"}),
-            ("/", "搜索"),
-            ("P", "代理"),
-            ("C", "链接"),
-            ("ESC", "退出"),
-        ]
-    }
-
-    async fn on_key(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Up => {
-                self.log_widget.select_up();
-                self.app_tx.send(AppEvent::Draw).unwrap();
-            }
-            KeyCode::Down => {
-                self.log_widget.select_down();
-                self.app_tx.send(AppEvent::Draw).unwrap();
-            }
-            KeyCode::Char('p') | KeyCode::Char('P') => {
-                self.app_tx.send(AppEvent::ShowGroupPage).unwrap();
-                self.inactive().await;
-            }
-            KeyCode::Char('c') | KeyCode::Char('C') => {
-                self.app_tx.send(AppEvent::ShowConnection).unwrap();
-                self.inactive().await;
-            }
-            KeyCode::Char(' ') => {
-                self.pause = !self.pause;
-                self.app_tx.send(AppEvent::Status(if self.pause {"暂停"} else {"恢复"}.to_owned())).unwrap();
-            }
-            KeyCode::Esc => {
-                self.app_tx.send(AppEvent::Quit).unwrap();
-            }
-            _ => {},
-        }
+impl Page for LogPage {
+    fn id(&self) -> &'static str {
+        Self::ID
     }
 
-    fn show(&mut self, area: Rect, buf: &mut Buffer) {
-        self.log_widget.render(area, buf);
+    fn title(&self) -> &'static str {
+        t(Msg::Logs)
     }
 
-    async fn active(&mut self) {
+    fn activate(&mut self, _param: Option<&str>) {
         self.pause = false;
-        let config = get_config();
-        let url = format!("ws://{}/logs", config.host);
-        let params = [("level", "info"), ("token", &config.key)];
-        let url = reqwest::Url::parse_with_params(&url, &params).unwrap();
+        self.log_level = get_config().settings.log_level;
+        let url = match self.api.ws_url("/logs", &[("level", self.log_level.as_str())]) {
+            Ok(url) => url,
+            Err(e) => {
+                self.close_tx = None;
+                self.app_tx.send(AppEvent::Status(tf(Msg::LoadDataError, &[&e]))).unwrap();
+                return;
+            }
+        };
 
         let (tx, rx) = channel::<bool>(1);
         // 重新赋值，则旧的sender会drop，这样在receiver也会关闭，那么async{}就会退出循环，并且结束
@@ -96,10 +83,10 @@ impl FilterInnerWidget for LogPage {
         start_ws_worker(url, rx, move |wsmsg| {
             match wsmsg {
                 WsMsg::ConnectFail(s) => {
-                    app_tx.send(AppEvent::Log(LogItem{r#type: "".into(), payload: format!("连接失败，重试: {s}")})).unwrap();
+                    app_tx.send(AppEvent::Log(LogItem{r#type: "".into(), payload: tf(Msg::ConnectFailRetry, &[&s])})).unwrap();
                 },
                 WsMsg::Closed => {
-                    app_tx.send(AppEvent::Log(LogItem{r#type: "".into(), payload: "连接已断开，重连".into()})).unwrap();
+                    app_tx.send(AppEvent::Log(LogItem{r#type: "".into(), payload: t(Msg::Disconnected).into()})).unwrap();
                 }
                 WsMsg::Message(msg) => {
                     let log = serde_json::from_str::<LogItem>(msg.to_text().unwrap()).unwrap();
@@ -109,12 +96,97 @@ impl FilterInnerWidget for LogPage {
         });
     }
 
-    fn on_data(&mut self, data: Box<dyn Any>) {
-        if self.pause {
+    // 保留已有的日志和滚动位置，返回时继续显示
+    fn deactivate(&mut self) {
+        self.close_tx = None;
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) {
+        if let Some(scroll) = keymap::find(&key_event, &Scroll::ACTIONS).and_then(Scroll::from_action) {
+            self.log_widget.scroll(scroll);
+            self.app_tx.send(AppEvent::Draw).unwrap();
             return;
         }
-        if let Ok(data) = data.downcast::<LogItem>() {
-            self.log_widget.add_line(data.payload);
+        match keymap::find(&key_event, &Self::ACTIONS) {
+            Some(Action::ShowProxy) => {
+                self.app_tx.send(AppEvent::ShowPage(GroupPage::ID, None)).unwrap();
+            }
+            Some(Action::ShowConnection) => {
+                self.app_tx.send(AppEvent::ShowPage(ConnectionPage::ID, None)).unwrap();
+            }
+            Some(Action::Pause) => {
+                self.pause = !self.pause;
+                self.app_tx.send(AppEvent::Status(t(if self.pause {Msg::Pause} else {Msg::Resume}).to_owned())).unwrap();
+            }
+            Some(Action::Quit) => {
+                self.app_tx.send(AppEvent::Quit).unwrap();
+            }
+            _ => {},
+        }
+    }
+
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        if let Some(scroll) = Scroll::from_mouse(mouse_event.kind) {
+            self.log_widget.scroll(scroll);
+            self.app_tx.send(AppEvent::Draw).unwrap();
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.log_widget.render(area, buf);
+    }
+
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::Pause, t(if self.pause {Msg::Resume} else {Msg::Pause})),
+            (Action::Filter, t(Msg::Search)),
+            (Action::Detail, t(Msg::Detail)),
+            (Action::ShowProxy, t(Msg::Proxies)),
+            (Action::ShowConnection, t(Msg::Connections)),
+            (Action::Quit, t(Msg::Quit)),
+        ])
+    }
+
+    fn actions(&self) -> Vec<Action> {
+        [Scroll::ACTIONS.as_slice(), &Self::ACTIONS].concat()
+    }
+
+    fn on_data(&mut self, data: &dyn Any) {
+        if let Some(log) = data.downcast_ref::<LogItem>() {
+            if !self.pause {
+                self.log_widget.add_line(log.payload.clone());
+            }
+        } else if let Some(config) = data.downcast_ref::<Config>() {
+            self.log_widget.set_max(config.settings.log.buffer_size);
+            if self.close_tx.is_some() && config.settings.log_level != self.log_level {
+                self.activate(None);
+            }
+        }
+    }
+
+    fn set_api(&mut self, api: ClashApi) {
+        self.api = api;
+        self.log_widget.clear();
+    }
+
+    fn set_filter(&mut self, filter: &Query) {
+        self.log_widget.set_filter(filter);
+    }
+
+    // 链接的日志额外显示解析出的各个字段
+    fn detail(&self) -> Option<Vec<DetailSection>> {
+        let Some(line) = self.log_widget.current_line() else {
+            return Some(vec![]);
+        };
+        let mut section = DetailSection::new(t(Msg::DetailGeneral)).item(t(Msg::DetailMessage), line);
+        if let Some(caps) = CONNECTION_LOG.captures(line) {
+            let names = [Msg::DetailNetwork, Msg::SourceHost, Msg::DetailDestination, Msg::Rule, Msg::Chain];
+            for (name, value) in names.into_iter().zip(caps.iter().skip(1)) {
+                if let Some(value) = value {
+                    section = section.item(t(name), value.as_str());
+                }
+            }
         }
+        Some(vec![section])
     }
-}
\ No newline at end of file
+}
@@ -3,16 +3,69 @@ mod group_page;
 mod log_page;
 pub mod widget;
 mod connection_page;
+mod backend_page;
+mod registry;
+pub mod operation;
 
+use std::any::Any;
 use std::time::Duration;
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
 use futures_util::StreamExt as _;
 pub use group_item_page::GroupItemPage;
 pub use group_page::GroupPage;
 pub use log_page::LogPage;
 pub use connection_page::ConnectionPage;
+pub use backend_page::{BackendPage, BackendStatus};
+pub use registry::PageRegistry;
 use tokio::{select, sync::mpsc::Receiver};
 use tokio_tungstenite::{connect_async, tungstenite::Message};
 use url::Url;
+use crate::clash_api::{redact, ClashApi};
+use crate::keymap::Action;
+use crate::page::widget::{Query, TableWidget};
+use crate::page::widget::detail_widget::DetailSection;
+
+// 所有界面的公共接口，App通过它切换界面、分发按键和数据，新的界面实现它并注册到PageRegistry即可
+pub trait Page {
+    // 切换界面时使用的标识，也是命令面板中page命令的参数
+    fn id(&self) -> &'static str;
+    // 标签栏中显示的名称
+    fn title(&self) -> &'static str;
+    // 子界面所属的界面，子界面不在标签栏中显示，显示时标签栏选中所属的界面
+    fn parent(&self) -> Option<&'static str> {
+        None
+    }
+    // 进入界面，param为切换界面时附带的参数，比如分组名称
+    fn activate(&mut self, param: Option<&str>);
+    // 离开界面，停止数据订阅
+    fn deactivate(&mut self) {}
+    fn on_key(&mut self, key_event: KeyEvent);
+    fn on_mouse(&mut self, mouse_event: MouseEvent);
+    // 粘贴的文字，只有正在输入时处理
+    fn on_paste(&mut self, _text: &str) {}
+    fn render(&mut self, area: Rect, buf: &mut Buffer);
+    fn get_menu(&self) -> Vec<(String, &'static str)>;
+    // 当前界面和模式下处理的动作，用于生成帮助
+    fn actions(&self) -> Vec<Action>;
+    // 后台推送的数据会发给所有界面，每个界面只处理自己关心的类型
+    fn on_data(&mut self, _data: &dyn Any) {}
+    fn set_api(&mut self, api: ClashApi);
+    fn set_filter(&mut self, filter: &Query);
+    // 正在输入文字，此时字符不作为快捷键
+    fn is_editing(&self) -> bool {
+        false
+    }
+    // 界面中的表格，用于列设置
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        None
+    }
+    // 选中行的详情，None表示界面没有详情，没有选中的行时返回空的列表
+    fn detail(&self) -> Option<Vec<DetailSection>> {
+        None
+    }
+}
 
 pub enum WsMsg {
     ConnectFail(String),
@@ -23,6 +76,10 @@ pub enum WsMsg {
 pub fn start_ws_worker<F>(url: Url, mut rx: Receiver<bool>, f: F)
     where
         F: Fn(WsMsg)  + Send + Sync + 'static {
+    let secret = url.query_pairs()
+        .find(|(k, _)| k == "token")
+        .map(|(_, v)| v.to_string())
+        .unwrap_or_default();
     tokio::spawn(async move {
         'o: loop {
             let mut ws_stream = loop {
@@ -32,7 +89,7 @@ pub fn start_ws_worker<F>(url: Url, mut rx: Receiver<bool>, f: F)
                             Ok(ws) => break ws.0,
                             Err(e) => {
                                 tokio::time::sleep(Duration::from_secs(1)).await;
-                                f(WsMsg::ConnectFail(e.to_string()));
+                                f(WsMsg::ConnectFail(redact(&e.to_string(), &secret)));
                                 continue;
                             }
                         }
@@ -47,7 +104,7 @@ pub fn start_ws_worker<F>(url: Url, mut rx: Receiver<bool>, f: F)
                             Some(Ok(msg)) => f(WsMsg::Message(msg)),
                             Some(Err(e)) => {
                                 tokio::time::sleep(Duration::from_secs(1)).await;
-                                f(WsMsg::ConnectFail(e.to_string()));
+                                f(WsMsg::ConnectFail(redact(&e.to_string(), &secret)));
                                 break;
                             },
                             None => {
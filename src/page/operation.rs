@@ -0,0 +1,119 @@
+// 按键和命令面板共用的后台操作，结果通过AppEvent返回
+use crate::app_config::get_config;
+use crate::clash_api::{ClashApi, ConnectionItem};
+use crate::my_event::AppEvent;
+use crate::i18n::{t, tf, Msg};
+use crate::my_event::AppEvent::{ModeChanged, ProxyLoaded, Status};
+use futures_util::future::join_all;
+use tokio::sync::mpsc::UnboundedSender;
+
+pub fn set_mode(api: ClashApi, app_tx: UnboundedSender<AppEvent>, mode: String) {
+    tokio::spawn(async move {
+        if let Err(err) = api.set_mode(&mode).await {
+            app_tx.send(Status(tf(Msg::SetModeError, &[&err]))).unwrap();
+        } else {
+            match api.get_mode().await {
+                Ok(mode) => {
+                    app_tx.send(ModeChanged(mode)).unwrap();
+                },
+                Err(e) => {
+                    app_tx.send(Status(tf(Msg::LoadDataError, &[&e]))).unwrap();
+                },
+            };
+        }
+    });
+}
+
+// 将节点设为分组的当前节点
+pub fn select_proxy(api: ClashApi, app_tx: UnboundedSender<AppEvent>, group: String, proxy: String) {
+    tokio::spawn(async move {
+        if let Err(err) = api.select_group_current(&group, &proxy).await {
+            app_tx.send(Status(tf(Msg::SelectProxyError, &[&err]))).unwrap();
+        }
+        load_proxy(&api, &app_tx).await;
+    });
+}
+
+// 测试分组中所有节点的延迟
+pub fn test_delay(api: ClashApi, app_tx: UnboundedSender<AppEvent>, group: String) {
+    let delay = get_config().settings.delay;
+    tokio::spawn(async move {
+        app_tx.send(Status(t(Msg::Testing).into())).unwrap();
+        match api.check_delay(&group, &delay.url, delay.timeout).await {
+            Ok(_) => {}
+            Err(e) => {app_tx.send(Status(tf(Msg::TestDelayError, &[&e]))).unwrap();}
+        }
+        if load_proxy(&api, &app_tx).await {
+            app_tx.send(Status(t(Msg::TestDone).into())).unwrap();
+        }
+    });
+}
+
+// 测试几个节点的延迟，超时的节点刷新后显示为超时，全部失败时多半是无法连接后端，提示错误
+pub fn test_proxies_delay(api: ClashApi, app_tx: UnboundedSender<AppEvent>, proxies: Vec<String>) {
+    let delay = get_config().settings.delay;
+    tokio::spawn(async move {
+        app_tx.send(Status(t(Msg::Testing).into())).unwrap();
+        let tests = proxies.iter().map(|x| api.check_proxy_delay(x, &delay.url, delay.timeout));
+        let results = join_all(tests).await;
+        if let Some(Err(e)) = results.first().filter(|_| results.iter().all(Result::is_err)) {
+            app_tx.send(Status(tf(Msg::TestDelayError, &[&e]))).unwrap();
+        }
+        if load_proxy(&api, &app_tx).await {
+            app_tx.send(Status(t(Msg::TestDone).into())).unwrap();
+        }
+    });
+}
+
+// 加载代理数据，成功时返回true
+pub async fn load_proxy(api: &ClashApi, app_tx: &UnboundedSender<AppEvent>) -> bool {
+    match api.load_proxy().await {
+        Ok(proxy) => {
+            app_tx.send(ProxyLoaded(proxy)).unwrap();
+            true
+        },
+        Err(e) => {
+            app_tx.send(Status(tf(Msg::LoadDataError, &[&e]))).unwrap();
+            false
+        }
+    }
+}
+
+// 关闭满足条件的链接，条件为all、id:链接ID(多个用逗号分隔)、host:主机，其余的在主机、目标IP、链路、规则中查找
+pub fn close_connections(api: ClashApi, app_tx: UnboundedSender<AppEvent>, filter: String) {
+    tokio::spawn(async move {
+        let result = if filter == "all" {
+            api.close_all_connections().await.map(|_| t(Msg::ClosedAll).to_string())
+        } else {
+            close_matching(&api, &filter).await.map(|n| tf(Msg::ClosedCount, &[&n]))
+        };
+        let msg = result.unwrap_or_else(|e| tf(Msg::CloseError, &[&e]));
+        app_tx.send(Status(msg)).unwrap();
+    });
+}
+
+async fn close_matching(api: &ClashApi, filter: &str) -> anyhow::Result<usize> {
+    let connection = api.load_connections().await?;
+    let items = connection.connections
+        .iter()
+        .filter(|x| match_connection(x, filter))
+        .collect::<Vec<_>>();
+    for item in &items {
+        api.close_connection(&item.id).await?;
+    }
+    Ok(items.len())
+}
+
+fn match_connection(item: &ConnectionItem, filter: &str) -> bool {
+    // 多个ID用逗号分隔
+    if let Some(ids) = filter.strip_prefix("id:") {
+        return ids.split(',').any(|id| item.id == id);
+    }
+    if let Some(host) = filter.strip_prefix("host:") {
+        return item.metadata.host.contains(host) || item.metadata.destination_ip.contains(host);
+    }
+    item.metadata.host.contains(filter)
+        || item.metadata.destination_ip.contains(filter)
+        || item.chains.iter().any(|x| x.contains(filter))
+        || item.rule.contains(filter)
+}
@@ -0,0 +1,99 @@
+use std::any::Any;
+use crate::page::Page;
+
+// 最多记录的导航历史
+const MAX_HISTORY: usize = 50;
+
+// 所有的界面和导航栈
+pub struct PageRegistry {
+    pages: Vec<Box<dyn Page>>,
+    current: usize,
+    // 之前访问的界面，返回时从这里取
+    history: Vec<usize>,
+}
+
+impl PageRegistry {
+    pub fn new(pages: Vec<Box<dyn Page>>) -> Self {
+        Self {
+            pages,
+            current: 0,
+            history: vec![],
+        }
+    }
+
+    pub fn current(&self) -> &dyn Page {
+        self.pages[self.current].as_ref()
+    }
+
+    pub fn current_mut(&mut self) -> &mut dyn Page {
+        self.pages[self.current].as_mut()
+    }
+
+    // 切换到id对应的界面，界面不存在时返回false
+    pub fn show(&mut self, id: &str, param: Option<&str>) -> bool {
+        let Some(index) = self.pages.iter().position(|x| x.id() == id) else {
+            return false;
+        };
+        self.pages[self.current].deactivate();
+        if index != self.current {
+            self.history.push(self.current);
+            if self.history.len() > MAX_HISTORY {
+                self.history.remove(0);
+            }
+            self.current = index;
+        }
+        self.pages[index].activate(param);
+        true
+    }
+
+    // 返回上一个界面，没有历史时返回false
+    pub fn back(&mut self) -> bool {
+        let Some(index) = self.history.pop() else {
+            return false;
+        };
+        self.pages[self.current].deactivate();
+        self.current = index;
+        self.pages[index].activate(None);
+        true
+    }
+
+    // 标签栏中的界面，子界面除外
+    pub fn tabs(&self) -> Vec<&dyn Page> {
+        self.pages.iter().filter(|x| x.parent().is_none()).map(|x| x.as_ref()).collect()
+    }
+
+    // 当前界面在标签栏中的位置，子界面对应所属界面的位置
+    pub fn current_tab(&self) -> Option<usize> {
+        let current = self.current();
+        let id = current.parent().unwrap_or(current.id());
+        self.tabs().iter().position(|x| x.id() == id)
+    }
+
+    // 切换到标签栏中的第index个界面，已经在这个界面时不做处理
+    pub fn show_tab(&mut self, index: usize) -> bool {
+        let Some(id) = self.tabs().get(index).map(|x| x.id()) else {
+            return false;
+        };
+        if id == self.current().id() {
+            return false;
+        }
+        self.show(id, None)
+    }
+
+    // 重新进入当前界面，用于后端变化后重新加载数据
+    pub fn reactivate(&mut self) {
+        let page = self.current_mut();
+        page.deactivate();
+        page.activate(None);
+    }
+
+    pub fn broadcast(&mut self, data: &dyn Any) {
+        for page in &mut self.pages {
+            page.on_data(data);
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Page>> {
+        self.pages.iter_mut()
+    }
+}
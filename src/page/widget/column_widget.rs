@@ -0,0 +1,327 @@
+use std::any::Any;
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph, Widget};
+use tokio::sync::mpsc::UnboundedSender;
+use crate::app_config::Config;
+use crate::clash_api::ClashApi;
+use crate::columns;
+use crate::g;
+use crate::my_event::AppEvent;
+use crate::page::Page;
+use crate::page::widget::{Query, TableWidget};
+use crate::theme::theme;
+use crate::i18n::{t, tf, Msg};
+use crate::keymap::{self, Action};
+
+// 固定宽度的上限
+const MAX_COLUMN_WIDTH: u16 = 200;
+
+// 给有表格的界面加上列设置：显示或隐藏、调整顺序、固定和宽度，关闭时保存到配置目录
+pub struct ColumnWidget<T: Page> {
+    app_tx: UnboundedSender<AppEvent>,
+    // 列设置中选中的列，None时没有打开列设置
+    cursor: Option<usize>,
+    // 打开列设置后是否有修改，有修改时关闭才保存
+    changed: bool,
+    has_table: bool,
+
+    inner_widget: T,
+}
+
+impl<T: Page> ColumnWidget<T> {
+    const ACTIONS_NORMAL: [Action; 1] = [Action::Columns];
+    const ACTIONS_EDIT: [Action; 13] = [
+        Action::Up,
+        Action::Down,
+        Action::Top,
+        Action::Bottom,
+        Action::ColumnToggle,
+        Action::ColumnMoveUp,
+        Action::ColumnMoveDown,
+        Action::ColumnPin,
+        Action::ColumnWider,
+        Action::ColumnNarrower,
+        Action::ColumnAutoWidth,
+        Action::ColumnReset,
+        Action::ColumnsClose,
+    ];
+
+    pub fn new(app_tx: UnboundedSender<AppEvent>, mut inner: T) -> ColumnWidget<T> {
+        let id = inner.id();
+        let has_table = match inner.table_mut() {
+            Some(table) => {
+                if let Some(layout) = columns::get(id) {
+                    table.set_layout(&layout);
+                }
+                true
+            }
+            None => false,
+        };
+        Self {
+            app_tx,
+            cursor: None,
+            changed: false,
+            has_table,
+
+            inner_widget: inner,
+        }
+    }
+
+    pub fn get_menu_columns() -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::ColumnToggle, t(Msg::ShowColumn)),
+            (Action::ColumnMoveUp, t(Msg::MoveUp)),
+            (Action::ColumnMoveDown, t(Msg::MoveDown)),
+            (Action::ColumnPin, t(Msg::Pin)),
+            (Action::ColumnWider, t(Msg::Wider)),
+            (Action::ColumnNarrower, t(Msg::Narrower)),
+            (Action::ColumnAutoWidth, t(Msg::AutoWidth)),
+            (Action::ColumnReset, t(Msg::Reset)),
+            (Action::ColumnsClose, t(Msg::Close)),
+        ])
+    }
+
+    fn close(&mut self) {
+        self.cursor = None;
+        if std::mem::take(&mut self.changed) {
+            let id = self.inner_widget.id();
+            if let Some(table) = self.inner_widget.table_mut() {
+                let layout = (!table.is_default_layout()).then(|| table.layout().to_vec());
+                if let Err(e) = columns::save(id, layout) {
+                    self.app_tx.send(AppEvent::Status(tf(Msg::SaveColumnsError, &[&format!("{e:#}")]))).unwrap();
+                }
+            }
+        }
+        self.app_tx.send(AppEvent::SetMenu(self.inner_widget.get_menu())).unwrap();
+    }
+
+    // 修改选中的列，返回修改后选中的列
+    fn update(table: &mut TableWidget, cursor: usize, action: Action) -> usize {
+        let name = table.layout()[cursor].name.clone();
+        let natural = table.natural_width(&name);
+        let last = table.layout().len() - 1;
+        let layout = table.layout_mut();
+        let cursor = match action {
+            Action::Up => cursor.saturating_sub(1),
+            Action::Down => (cursor + 1).min(last),
+            Action::Top => 0,
+            Action::Bottom => last,
+            Action::ColumnToggle => {
+                // 至少显示一列
+                if layout[cursor].hidden || layout.iter().filter(|x| !x.hidden).count() > 1 {
+                    layout[cursor].hidden = !layout[cursor].hidden;
+                }
+                cursor
+            }
+            Action::ColumnMoveUp if cursor > 0 => {
+                layout.swap(cursor, cursor - 1);
+                cursor - 1
+            }
+            Action::ColumnMoveDown if cursor < last => {
+                layout.swap(cursor, cursor + 1);
+                cursor + 1
+            }
+            Action::ColumnPin => {
+                layout[cursor].pinned = !layout[cursor].pinned;
+                cursor
+            }
+            Action::ColumnWider => {
+                let width = layout[cursor].width.unwrap_or(natural);
+                layout[cursor].width = Some((width + 1).min(MAX_COLUMN_WIDTH));
+                cursor
+            }
+            Action::ColumnNarrower => {
+                let width = layout[cursor].width.unwrap_or(natural);
+                layout[cursor].width = Some(width.saturating_sub(1).max(1));
+                cursor
+            }
+            Action::ColumnAutoWidth => {
+                layout[cursor].width = None;
+                cursor
+            }
+            Action::ColumnReset => {
+                table.reset_layout();
+                cursor
+            }
+            _ => cursor,
+        };
+        // 固定的列总是在前面，列设置中的顺序与显示的顺序一致
+        let layout = table.layout_mut();
+        layout.sort_by_key(|x| !x.pinned);
+        layout.iter().position(|x| x.name == name).unwrap_or(cursor)
+    }
+
+    fn render_columns(&mut self, cursor: usize, area: Rect, buf: &mut Buffer) {
+        let Some(table) = self.inner_widget.table_mut() else {
+            return;
+        };
+        let theme = theme();
+        let title_width = table.layout()
+            .iter()
+            .map(|x| g::string_width(table.column_title(&x.name)))
+            .max()
+            .unwrap_or(0);
+        let lines = table.layout()
+            .iter()
+            .enumerate()
+            .map(|(i, x)| {
+                let title = table.column_title(&x.name);
+                let width = x.width.map_or(t(Msg::ColumnAuto).to_string(), |x| x.to_string());
+                let line = Line::from(vec![
+                    Span::styled(if x.hidden { "[ ] " } else { "[✓] " }, Style::default().fg(theme.checked_fg)),
+                    Span::raw(title),
+                    Span::raw(" ".repeat(title_width - g::string_width(title) + 2)),
+                    Span::raw(format!("{width}{}", " ".repeat(6usize.saturating_sub(g::string_width(&width))))),
+                    Span::raw(if x.pinned { t(Msg::ColumnPinned) } else { "" }),
+                ]);
+                match i == cursor {
+                    true => line.add_modifier(Modifier::REVERSED).fg(theme.selected_fg),
+                    false => line,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+        let height = lines.len() as u16 + 2;
+        let width = width.min(area.width);
+        let height = height.min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        // 列很多时滚动，保证选中的列可见
+        let scroll = (cursor as u16).saturating_sub(height.saturating_sub(3));
+
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .scroll((scroll, 0))
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(format!(" {} ", t(Msg::ColumnsTitle)))
+                    .title_alignment(Alignment::Center)
+                    .padding(Padding::horizontal(1)),
+            )
+            .bg(theme.buffer_bg)
+            .fg(theme.row_fg)
+            .render(popup, buf);
+    }
+}
+
+impl<T: Page> Page for ColumnWidget<T> {
+    fn id(&self) -> &'static str {
+        self.inner_widget.id()
+    }
+
+    fn title(&self) -> &'static str {
+        self.inner_widget.title()
+    }
+
+    fn parent(&self) -> Option<&'static str> {
+        self.inner_widget.parent()
+    }
+
+    fn activate(&mut self, param: Option<&str>) {
+        self.inner_widget.activate(param);
+    }
+
+    // 离开界面时关闭列设置，已有的修改会保存
+    fn deactivate(&mut self) {
+        if self.cursor.is_some() {
+            self.close();
+        }
+        self.inner_widget.deactivate();
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) {
+        match self.cursor {
+            None => {
+                let open = self.has_table
+                    && !self.inner_widget.is_editing()
+                    && keymap::find(&key_event, &Self::ACTIONS_NORMAL).is_some();
+                if open {
+                    self.cursor = Some(0);
+                    self.app_tx.send(AppEvent::SetMenu(Self::get_menu_columns())).unwrap();
+                } else {
+                    self.inner_widget.on_key(key_event);
+                }
+            }
+            Some(cursor) => match keymap::find(&key_event, &Self::ACTIONS_EDIT) {
+                Some(Action::ColumnsClose) => self.close(),
+                Some(action) => {
+                    if let Some(table) = self.inner_widget.table_mut() {
+                        self.cursor = Some(Self::update(table, cursor, action));
+                        self.changed |= !matches!(action, Action::Up | Action::Down | Action::Top | Action::Bottom);
+                    }
+                    self.app_tx.send(AppEvent::Draw).unwrap();
+                }
+                None => {}
+            },
+        }
+    }
+
+    // 打开列设置时不处理鼠标
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        if self.cursor.is_none() {
+            self.inner_widget.on_mouse(mouse_event)
+        }
+    }
+
+    fn on_paste(&mut self, text: &str) {
+        if self.cursor.is_none() {
+            self.inner_widget.on_paste(text)
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.inner_widget.render(area, buf);
+        if let Some(cursor) = self.cursor {
+            self.render_columns(cursor, area, buf);
+        }
+    }
+
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        match self.cursor {
+            None => self.inner_widget.get_menu(),
+            Some(_) => Self::get_menu_columns(),
+        }
+    }
+
+    fn actions(&self) -> Vec<Action> {
+        match self.cursor {
+            None if self.has_table => [Self::ACTIONS_NORMAL.as_slice(), &self.inner_widget.actions()].concat(),
+            None => self.inner_widget.actions(),
+            Some(_) => Self::ACTIONS_EDIT.to_vec(),
+        }
+    }
+
+    fn on_data(&mut self, data: &dyn Any) {
+        if let (Some(config), Some(table)) = (data.downcast_ref::<Config>(), self.inner_widget.table_mut()) {
+            table.set_max_column_width(config.settings.table.max_column_width);
+        }
+        self.inner_widget.on_data(data)
+    }
+
+    fn set_api(&mut self, api: ClashApi) {
+        self.inner_widget.set_api(api)
+    }
+
+    fn set_filter(&mut self, filter: &Query) {
+        self.inner_widget.set_filter(filter);
+    }
+
+    // 打开列设置时所有的按键都由它处理
+    fn is_editing(&self) -> bool {
+        self.cursor.is_some() || self.inner_widget.is_editing()
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        self.inner_widget.table_mut()
+    }
+}
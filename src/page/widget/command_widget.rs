@@ -0,0 +1,178 @@
+use crate::command::fuzzy_match;
+use crate::g;
+use crate::keymap::{self, Action};
+use crate::page::widget::text_input::TextInput;
+use crate::theme::theme;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Clear, List, ListItem, ListState, Paragraph, StatefulWidget, Widget};
+
+// 最多显示的候选项
+const MAX_MATCHES: usize = 8;
+// 最多保存的历史命令
+const MAX_HISTORY: usize = 100;
+
+// 按:打开的命令面板
+#[derive(Default)]
+pub struct CommandWidget {
+    active: bool,
+    input: TextInput,
+    // 输入的内容，Tab补全时不变，用于匹配候选项
+    pattern: String,
+    history: Vec<String>,
+    // 正在浏览的历史命令
+    history_pos: Option<usize>,
+    candidates: Vec<String>,
+    // 与pattern匹配的候选项在candidates中的位置
+    matches: Vec<usize>,
+    // Tab选中的候选项
+    selected: Option<usize>,
+}
+
+impl CommandWidget {
+    pub fn open(&mut self, candidates: Vec<String>) {
+        self.active = true;
+        self.candidates = candidates;
+        self.history_pos = None;
+        self.set_input("");
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    // 按下回车时返回输入的命令
+    pub fn on_key(&mut self, key_event: KeyEvent) -> Option<String> {
+        match key_event.code {
+            KeyCode::Esc => self.active = false,
+            KeyCode::Enter => {
+                self.active = false;
+                let line = self.input.text().trim().to_string();
+                if line.is_empty() {
+                    return None;
+                }
+                if self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                    if self.history.len() > MAX_HISTORY {
+                        self.history.remove(0);
+                    }
+                }
+                return Some(line);
+            }
+            KeyCode::Tab => self.complete(true),
+            KeyCode::BackTab => self.complete(false),
+            _ if keymap::find(&key_event, &[Action::HistoryPrev]).is_some() => {
+                let pos = match self.history_pos {
+                    _ if self.history.is_empty() => return None,
+                    None => self.history.len() - 1,
+                    Some(i) => i.saturating_sub(1),
+                };
+                self.history_pos = Some(pos);
+                self.set_input(&self.history[pos].clone());
+            }
+            _ if keymap::find(&key_event, &[Action::HistoryNext]).is_some() => match self.history_pos {
+                Some(i) if i + 1 < self.history.len() => {
+                    self.history_pos = Some(i + 1);
+                    self.set_input(&self.history[i + 1].clone());
+                }
+                Some(_) => {
+                    self.history_pos = None;
+                    self.set_input("");
+                }
+                None => {}
+            },
+            _ => {
+                if self.input.on_key(&key_event) {
+                    self.update_matches();
+                }
+            }
+        }
+        None
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        self.history_pos = None;
+        self.input.insert(text);
+        self.update_matches();
+    }
+
+    fn set_input(&mut self, input: &str) {
+        self.input.set_text(input);
+        self.update_matches();
+    }
+
+    // 输入变化后重新匹配候选项
+    fn update_matches(&mut self) {
+        self.pattern = self.input.text().to_string();
+        self.selected = None;
+        let mut matches = self.candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| fuzzy_match(&self.pattern, x).map(|score| (score, x.len(), i)))
+            .collect::<Vec<_>>();
+        matches.sort();
+        self.matches = matches.into_iter().map(|(_, _, i)| i).collect();
+    }
+
+    // 用候选项替换输入，不改变匹配的结果
+    fn complete(&mut self, next: bool) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let count = self.matches.len().min(MAX_MATCHES);
+        let i = match self.selected {
+            None if next => 0,
+            None => count - 1,
+            Some(i) if next => (i + 1) % count,
+            Some(i) => (i + count - 1) % count,
+        };
+        self.selected = Some(i);
+        self.input.set_text(&self.candidates[self.matches[i]]);
+    }
+
+    // 在area的最下面一行显示输入，上面显示候选项
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.height < 2 {
+            return;
+        }
+        let theme = theme();
+        let input_area = Rect { y: area.bottom() - 1, height: 1, ..area };
+        Paragraph::new(format!(":{}", self.input.text()))
+            .fg(theme.filter_edit_fg)
+            .bg(theme.status_bg)
+            .render(input_area, buf);
+        // 光标
+        let x = input_area.x + 1 + g::string_width(self.input.before_cursor()) as u16;
+        if x < input_area.right() {
+            buf[(x, input_area.y)].set_style(Style::default().add_modifier(Modifier::REVERSED));
+        }
+
+        let items = self.matches
+            .iter()
+            .take(MAX_MATCHES)
+            .map(|i| self.candidates[*i].as_str())
+            .collect::<Vec<_>>();
+        if items.is_empty() {
+            return;
+        }
+        let width = items.iter().map(|x| g::string_width(x)).max().unwrap_or(0) as u16 + 2;
+        let height = (items.len() as u16 + 2).min(area.height - 1);
+        let list_area = Rect {
+            x: area.x,
+            y: input_area.y - height,
+            width: width.min(area.width),
+            height,
+        };
+        Clear.render(list_area, buf);
+        let list = List::new(items.into_iter().map(|x| ListItem::new(Line::from(x))))
+            .block(Block::bordered().border_type(BorderType::Rounded))
+            .bg(theme.buffer_bg)
+            .fg(theme.row_fg)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED).fg(theme.selected_fg));
+        let mut state = ListState::default().with_selected(self.selected);
+        StatefulWidget::render(list, list_area, buf, &mut state);
+    }
+}
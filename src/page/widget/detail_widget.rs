@@ -0,0 +1,295 @@
+use std::any::Any;
+use crossterm::event::{KeyEvent, MouseEvent};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph, Widget};
+use tokio::sync::mpsc::UnboundedSender;
+use crate::clash_api::ClashApi;
+use crate::g;
+use crate::my_event::AppEvent;
+use crate::page::Page;
+use crate::page::widget::{Query, Scroll, TableWidget};
+use crate::theme::theme;
+use crate::i18n::{t, tf, Msg};
+use crate::keymap::{self, Action};
+
+// 详情中的一组内容：标题和若干个名称、值
+pub struct DetailSection {
+    pub title: String,
+    pub items: Vec<(String, String)>,
+}
+
+impl DetailSection {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self { title: title.into(), items: vec![] }
+    }
+
+    pub fn item(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.items.push((name.into(), value.into()));
+        self
+    }
+}
+
+// 给界面加上详情弹窗：按i或Enter(界面没有用到Enter时)显示选中行的详情，可以滚动和复制每一项的值
+pub struct DetailWidget<T: Page> {
+    app_tx: UnboundedSender<AppEvent>,
+    // 打开的详情，None时没有打开
+    sections: Option<Vec<DetailSection>>,
+    // 选中的项，所有分组中的项按顺序编号
+    cursor: usize,
+    // 上次绘制时能显示的行数，用于翻页
+    page_size: usize,
+
+    inner_widget: T,
+}
+
+impl<T: Page> DetailWidget<T> {
+    const ACTIONS_NORMAL: [Action; 1] = [Action::Detail];
+    const ACTIONS_OPEN: [Action; 8] = [
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::Copy,
+        Action::DetailClose,
+    ];
+
+    pub fn new(app_tx: UnboundedSender<AppEvent>, inner: T) -> DetailWidget<T> {
+        Self {
+            app_tx,
+            sections: None,
+            cursor: 0,
+            page_size: 1,
+
+            inner_widget: inner,
+        }
+    }
+
+    pub fn get_menu_detail() -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::Copy, t(Msg::Copy)),
+            (Action::DetailClose, t(Msg::Close)),
+        ])
+    }
+
+    // 默认按键中的Enter只在界面没有用到它时打开详情，界面的动作优先
+    fn opens(&self, key_event: &KeyEvent) -> bool {
+        !self.inner_widget.is_editing()
+            && keymap::find(key_event, &Self::ACTIONS_NORMAL).is_some()
+            && keymap::find(key_event, &self.inner_widget.actions()).is_none()
+    }
+
+    fn open(&mut self) {
+        // 没有选中的行时不打开
+        let Some(sections) = self.inner_widget.detail().filter(|x| !x.is_empty()) else {
+            return;
+        };
+        self.sections = Some(sections);
+        self.cursor = 0;
+        self.app_tx.send(AppEvent::SetMenu(Self::get_menu_detail())).unwrap();
+    }
+
+    fn close(&mut self) {
+        self.sections = None;
+        self.app_tx.send(AppEvent::SetMenu(self.inner_widget.get_menu())).unwrap();
+    }
+
+    fn items(&self) -> impl Iterator<Item = &(String, String)> {
+        self.sections.iter().flatten().flat_map(|x| x.items.iter())
+    }
+
+    fn scroll(&mut self, scroll: Scroll) {
+        let last = self.items().count().saturating_sub(1);
+        self.cursor = match scroll {
+            Scroll::Up => self.cursor.saturating_sub(1),
+            Scroll::Down => self.cursor + 1,
+            Scroll::PageUp => self.cursor.saturating_sub(self.page_size),
+            Scroll::PageDown => self.cursor + self.page_size,
+            Scroll::Top => 0,
+            Scroll::Bottom => last,
+            Scroll::Left | Scroll::Right => self.cursor,
+        }
+        .min(last);
+    }
+
+    fn copy(&self) {
+        let Some((name, value)) = self.items().nth(self.cursor) else {
+            return;
+        };
+        self.app_tx.send(AppEvent::Copy(value.clone(), tf(Msg::CopiedValue, &[name]))).unwrap();
+    }
+
+    fn render_detail(&mut self, area: Rect, buf: &mut Buffer) {
+        let Some(sections) = &self.sections else {
+            return;
+        };
+        let theme = theme();
+        let name_width = self.items().map(|(name, _)| g::string_width(name)).max().unwrap_or(0);
+        let value_width = self.items().map(|(_, value)| g::string_width(value)).max().unwrap_or(0);
+        let title_width = sections.iter().map(|x| g::string_width(&x.title)).max().unwrap_or(0);
+        // 边框和两边的空白占4列，名称和值之间空2列
+        let width = (name_width + 2 + value_width).max(title_width).max(20) + 4;
+        let width = (width as u16).min(area.width);
+        let wrap_width = (width as usize).saturating_sub(4 + name_width + 2).max(1);
+
+        // 值太长时折行，后面的行与值对齐；记录每一项的第一行和最后一行，用于滚动到选中的项
+        let mut lines = vec![];
+        let mut item_lines = vec![];
+        for section in sections {
+            if !lines.is_empty() {
+                lines.push(Line::default());
+            }
+            lines.push(Line::from(section.title.as_str()).add_modifier(Modifier::BOLD).fg(theme.header_fg));
+            for (name, value) in &section.items {
+                let selected = item_lines.len() == self.cursor;
+                let start = lines.len();
+                for (i, range) in g::wrap_ranges(value, wrap_width).into_iter().enumerate() {
+                    let label = if i == 0 { name.as_str() } else { "" };
+                    let padding = " ".repeat(name_width - g::string_width(label) + 2);
+                    let line = Line::from(format!("{label}{padding}{}", &value[range]));
+                    lines.push(match selected {
+                        true => line.add_modifier(Modifier::REVERSED).fg(theme.selected_fg),
+                        false => line,
+                    });
+                }
+                item_lines.push((start, lines.len() - 1));
+            }
+        }
+
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        // 内容很多时滚动，保证选中的项可见
+        let visible = height.saturating_sub(2) as usize;
+        self.page_size = visible.max(1);
+        let scroll = item_lines
+            .get(self.cursor)
+            .map_or(0, |(_, end)| (end + 1).saturating_sub(visible));
+
+        Clear.render(popup, buf);
+        Paragraph::new(lines)
+            .scroll((scroll as u16, 0))
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(format!(" {} ", t(Msg::Detail)))
+                    .title_alignment(Alignment::Center)
+                    .padding(Padding::horizontal(1)),
+            )
+            .bg(theme.buffer_bg)
+            .fg(theme.row_fg)
+            .render(popup, buf);
+    }
+}
+
+impl<T: Page> Page for DetailWidget<T> {
+    fn id(&self) -> &'static str {
+        self.inner_widget.id()
+    }
+
+    fn title(&self) -> &'static str {
+        self.inner_widget.title()
+    }
+
+    fn parent(&self) -> Option<&'static str> {
+        self.inner_widget.parent()
+    }
+
+    fn activate(&mut self, param: Option<&str>) {
+        self.inner_widget.activate(param);
+    }
+
+    fn deactivate(&mut self) {
+        self.sections = None;
+        self.inner_widget.deactivate();
+    }
+
+    fn on_key(&mut self, key_event: KeyEvent) {
+        if self.sections.is_none() {
+            match self.opens(&key_event) {
+                true => self.open(),
+                false => self.inner_widget.on_key(key_event),
+            }
+            return;
+        }
+        match keymap::find(&key_event, &Self::ACTIONS_OPEN) {
+            Some(Action::DetailClose) => self.close(),
+            Some(Action::Copy) => self.copy(),
+            Some(action) => {
+                if let Some(scroll) = Scroll::from_action(action) {
+                    self.scroll(scroll);
+                    self.app_tx.send(AppEvent::Draw).unwrap();
+                }
+            }
+            None => {}
+        }
+    }
+
+    // 打开详情时只处理滚轮
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        if self.sections.is_none() {
+            self.inner_widget.on_mouse(mouse_event);
+        } else if let Some(scroll) = Scroll::from_mouse(mouse_event.kind) {
+            self.scroll(scroll);
+            self.app_tx.send(AppEvent::Draw).unwrap();
+        }
+    }
+
+    fn on_paste(&mut self, text: &str) {
+        if self.sections.is_none() {
+            self.inner_widget.on_paste(text);
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        self.inner_widget.render(area, buf);
+        self.render_detail(area, buf);
+    }
+
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        match self.sections {
+            None => self.inner_widget.get_menu(),
+            Some(_) => Self::get_menu_detail(),
+        }
+    }
+
+    fn actions(&self) -> Vec<Action> {
+        match self.sections {
+            None if self.inner_widget.detail().is_some() => {
+                [Self::ACTIONS_NORMAL.as_slice(), &self.inner_widget.actions()].concat()
+            }
+            None => self.inner_widget.actions(),
+            Some(_) => Self::ACTIONS_OPEN.to_vec(),
+        }
+    }
+
+    fn on_data(&mut self, data: &dyn Any) {
+        self.inner_widget.on_data(data)
+    }
+
+    fn set_api(&mut self, api: ClashApi) {
+        self.sections = None;
+        self.inner_widget.set_api(api)
+    }
+
+    fn set_filter(&mut self, filter: &Query) {
+        self.inner_widget.set_filter(filter);
+    }
+
+    // 打开详情时所有的按键都由它处理
+    fn is_editing(&self) -> bool {
+        self.sections.is_some() || self.inner_widget.is_editing()
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        self.inner_widget.table_mut()
+    }
+}
@@ -1,13 +1,27 @@
 use std::any::Any;
-use crossterm::event::{KeyCode, KeyEvent};
+use std::time::{Duration, Instant};
+use crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::prelude::{Line, Span};
-use ratatui::style::palette::tailwind;
-use ratatui::style::Stylize;
+use ratatui::style::{Modifier, Style, Stylize};
 use ratatui::widgets::{Block, Paragraph, Widget};
 use tokio::sync::mpsc::UnboundedSender;
+use crate::clash_api::ClashApi;
+use crate::g;
 use crate::my_event::AppEvent;
+use crate::page::Page;
+use crate::page::widget::{Query, TableWidget};
+use crate::page::widget::text_input::TextInput;
+use crate::theme::theme;
+use crate::i18n::{t, tf, Msg};
+use crate::keymap::{self, Action};
+
+// 最多保存的过滤条件
+const MAX_HISTORY: usize = 50;
+// 过滤一次比这个慢时，输入停顿后才应用过滤条件
+const SLOW_FILTER: Duration = Duration::from_millis(20);
+const DEBOUNCE: Duration = Duration::from_millis(200);
 
 #[derive(PartialEq)]
 enum Status {
@@ -15,114 +29,304 @@ enum Status {
     FilterEdit,
 }
 
-pub trait FilterInnerWidget {
-    fn set_filter(&mut self, filter: &str);
-    fn get_menu(&self) -> Vec<(&'static str, &'static str)>;
-    async fn on_key(&mut self, key_event: KeyEvent);
-    fn show(&mut self, area: Rect, buf: &mut Buffer);
-    async fn active(&mut self);
-    fn on_data(&mut self, data: Box<dyn Any>);
-}
-
-pub struct FilterWidget<T: FilterInnerWidget> {
+// 给界面加上过滤功能，所有的界面都用它包装后注册
+pub struct FilterWidget<T: Page> {
     app_tx: UnboundedSender<AppEvent>,
-    filter: String,
+    filter: TextInput,
+    // 过滤条件有错误时在过滤栏中显示
+    error: Option<String>,
     status: Status,
+    // 确认过的过滤条件，编辑时可以浏览
+    history: Vec<String>,
+    // 正在浏览的历史，以及浏览前输入的内容
+    history_pos: Option<usize>,
+    draft: String,
+    // 等待应用的过滤条件的时间，内容很多时输入停顿后才过滤
+    pending: Option<Instant>,
+    // 上次应用过滤条件和上次绘制所用的时间
+    filter_cost: Duration,
+    render_cost: Duration,
 
     inner_widget: T,
 }
 
-impl<T: FilterInnerWidget> FilterWidget<T> {
-    pub(crate) fn on_data(&mut self, data: Box<dyn Any>) {
-        self.inner_widget.on_data(data)
-
-    }
-    pub async fn active(&mut self) {
-        self.inner_widget.active().await;
-    }
-
-    pub fn get_menu(&self) -> Vec<(&'static str, &'static str)> {
-        T::get_menu(&self.inner_widget)
-    }
+impl<T: Page> FilterWidget<T> {
+    const ACTIONS_NORMAL: [Action; 1] = [Action::Filter];
+    const ACTIONS_EDIT: [Action; 2] = [Action::FilterConfirm, Action::FilterCancel];
+    const ACTIONS_HISTORY: [Action; 2] = [Action::HistoryPrev, Action::HistoryNext];
 
     pub fn new(app_tx: UnboundedSender<AppEvent>, inner: T) -> FilterWidget<T> {
         Self {
             app_tx,
             filter: Default::default(),
+            error: None,
             status: Status::Normal,
+            history: vec![],
+            history_pos: None,
+            draft: String::new(),
+            pending: None,
+            filter_cost: Duration::ZERO,
+            render_cost: Duration::ZERO,
 
             inner_widget: inner,
         }
     }
 
-    pub fn get_menu_filter_edit() -> Vec<(&'static str, &'static str)> {
-        vec![
-            ("ENTER", "确认"),
-            ("ESC", "放弃"),
-        ]
+    pub fn get_menu_filter_edit() -> Vec<(String, &'static str)> {
+        keymap::menu(&[
+            (Action::FilterConfirm, t(Msg::Confirm)),
+            (Action::FilterCancel, t(Msg::Discard)),
+        ])
+    }
+
+    // 有表格时过滤条件中可以使用表格的列名
+    fn parse(&mut self) -> Result<Query, String> {
+        let columns = self.inner_widget.table_mut().map(|x| x.column_names()).unwrap_or_default();
+        Query::parse(self.filter.text(), &columns)
+    }
+
+    fn apply(&mut self, query: &Query) {
+        let start = Instant::now();
+        self.pending = None;
+        self.inner_widget.set_filter(query);
+        self.filter_cost = start.elapsed();
+    }
+
+    // 输入时就过滤，过滤较慢时等输入停顿后再过滤，有错误时保留上一个正确的条件
+    fn on_edit(&mut self) {
+        match self.parse() {
+            Ok(query) => {
+                self.error = None;
+                if self.filter_cost + self.render_cost < SLOW_FILTER {
+                    self.apply(&query);
+                } else {
+                    self.pending = Some(Instant::now() + DEBOUNCE);
+                    let app_tx = self.app_tx.clone();
+                    tokio::spawn(async move {
+                        tokio::time::sleep(DEBOUNCE).await;
+                        app_tx.send(AppEvent::Draw).unwrap_or(());
+                    });
+                }
+            }
+            Err(e) => {
+                self.error = Some(e);
+                self.pending = None;
+            }
+        }
+        self.app_tx.send(AppEvent::Draw).unwrap();
+    }
+
+    fn add_history(&mut self) {
+        let text = self.filter.text().trim().to_string();
+        self.history_pos = None;
+        if text.is_empty() {
+            return;
+        }
+        self.history.retain(|x| *x != text);
+        self.history.push(text);
+        if self.history.len() > MAX_HISTORY {
+            self.history.remove(0);
+        }
+    }
+
+    // 浏览历史，回到最后时恢复浏览前输入的内容
+    fn browse_history(&mut self, up: bool) -> bool {
+        let pos = match (self.history_pos, up) {
+            _ if self.history.is_empty() => return false,
+            (None, true) => {
+                self.draft = self.filter.text().to_string();
+                Some(self.history.len() - 1)
+            }
+            (None, false) => return false,
+            (Some(i), true) => Some(i.saturating_sub(1)),
+            (Some(i), false) if i + 1 < self.history.len() => Some(i + 1),
+            (Some(_), false) => None,
+        };
+        self.history_pos = pos;
+        let text = match pos {
+            Some(i) => self.history[i].clone(),
+            None => std::mem::take(&mut self.draft),
+        };
+        self.filter.set_text(&text);
+        true
+    }
+}
+
+impl<T: Page> Page for FilterWidget<T> {
+    fn id(&self) -> &'static str {
+        self.inner_widget.id()
+    }
+
+    fn title(&self) -> &'static str {
+        self.inner_widget.title()
+    }
+
+    fn parent(&self) -> Option<&'static str> {
+        self.inner_widget.parent()
+    }
+
+    fn activate(&mut self, param: Option<&str>) {
+        self.inner_widget.activate(param);
+    }
+
+    fn deactivate(&mut self) {
+        self.inner_widget.deactivate();
     }
 
-    pub async fn on_key(&mut self, key_event: KeyEvent) {
+    fn on_key(&mut self, key_event: KeyEvent) {
         match self.status {
             Status::Normal => {
-                match key_event.code {
-                    KeyCode::Char('/') => {
+                match keymap::find(&key_event, &Self::ACTIONS_NORMAL) {
+                    // 在当前的过滤条件上继续编辑，里面的界面正在输入(比如打开了详情)时不打开
+                    Some(_) if !self.inner_widget.is_editing() => {
                         self.status = Status::FilterEdit;
-                        self.inner_widget.set_filter("");
+                        self.history_pos = None;
                         self.app_tx.send(AppEvent::SetMenu(Self::get_menu_filter_edit())).unwrap();
                     }
-                    _ => self.inner_widget.on_key(key_event).await
+                    _ => self.inner_widget.on_key(key_event)
                 }
             },
             Status::FilterEdit => {
-                match key_event.code {
-                    KeyCode::Enter => {
+                match keymap::find(&key_event, &Self::ACTIONS_EDIT) {
+                    // 过滤条件有错误时继续编辑
+                    Some(Action::FilterConfirm) => match self.parse() {
+                        Ok(query) => {
+                            self.status = Status::Normal;
+                            self.error = None;
+                            self.add_history();
+                            self.apply(&query);
+                            self.app_tx.send(AppEvent::SetMenu(self.inner_widget.get_menu())).unwrap();
+                        }
+                        Err(e) => {
+                            self.error = Some(e);
+                            self.app_tx.send(AppEvent::Draw).unwrap();
+                        }
+                    },
+                    Some(_) => {
                         self.status = Status::Normal;
-                        self.inner_widget.set_filter(&self.filter);
-                        self.app_tx.send(AppEvent::SetMenu(T::get_menu(&self.inner_widget))).unwrap();
+                        self.filter.set_text("");
+                        self.history_pos = None;
+                        self.error = None;
+                        self.apply(&Query::default());
+                        self.app_tx.send(AppEvent::SetMenu(self.inner_widget.get_menu())).unwrap();
                     }
-                    KeyCode::Backspace => {
-                        self.filter.pop();
-                        self.app_tx.send(AppEvent::Draw).unwrap();
+                    None => {
+                        let before = self.filter.text().to_string();
+                        let handled = match keymap::find(&key_event, &Self::ACTIONS_HISTORY) {
+                            Some(action) => self.browse_history(action == Action::HistoryPrev),
+                            None => self.filter.on_key(&key_event),
+                        };
+                        if !handled {
+                            return;
+                        }
+                        if self.filter.text() != before {
+                            self.on_edit();
+                        } else {
+                            // 只移动了光标
+                            self.app_tx.send(AppEvent::Draw).unwrap();
+                        }
                     }
-                    KeyCode::Esc => {
-                        self.status = Status::Normal;
-                        self.filter.clear();
-                        self.inner_widget.set_filter("");
-                        self.app_tx.send(AppEvent::SetMenu(T::get_menu(&self.inner_widget))).unwrap();
-                    }
-                    KeyCode::Char(c) => {
-                        self.filter.push(c);
-                        self.app_tx.send(AppEvent::Draw).unwrap();
-                    }
-                    _ => {},
                 }
             }
         }
     }
 
-    pub fn show(&mut self, area: Rect, buf: &mut Buffer) {
-        if self.filter != "" || self.status == Status::FilterEdit {
+    fn on_mouse(&mut self, mouse_event: MouseEvent) {
+        self.inner_widget.on_mouse(mouse_event)
+    }
+
+    fn on_paste(&mut self, text: &str) {
+        match self.status {
+            Status::Normal => self.inner_widget.on_paste(text),
+            Status::FilterEdit => {
+                self.filter.insert(text);
+                self.on_edit();
+            }
+        }
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        // 输入停顿后应用等待中的过滤条件
+        if self.pending.is_some_and(|x| x <= Instant::now()) {
+            if let Ok(query) = self.parse() {
+                self.apply(&query);
+            }
+        }
+        let start = Instant::now();
+        if !self.filter.text().is_empty() || self.status == Status::FilterEdit {
             let layout = Layout::default()
                 .direction(Direction::Vertical)
                 .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
                 .split(area);
-            self.inner_widget.show(layout[0], buf);
+            self.inner_widget.render(layout[0], buf);
+            self.render_cost = start.elapsed();
 
+            let theme = theme();
             let fg = if self.status == Status::FilterEdit {
-                tailwind::GREEN.c600
+                theme.filter_edit_fg
             } else {
-                tailwind::BLACK
+                theme.status_fg
             };
 
-            let line = Line::from(vec![Span::raw(format!("过滤: {}", self.filter.clone()))]);
+            let mut spans = vec![Span::raw(tf(Msg::Filter, &[&self.filter.text()]))];
+            if let Some(error) = &self.error {
+                spans.push(Span::raw("  "));
+                spans.push(Span::raw(error.as_str()).fg(theme.menu_key_fg));
+            }
+            let line = Line::from(spans);
             let p_for_msg = Paragraph::new(line)
                 .alignment(Alignment::Left)
                 .fg(fg)
-                .block(Block::new().bg(tailwind::SLATE.c200));
+                .block(Block::new().bg(theme.status_bg));
             p_for_msg.render(layout[1], buf);
+
+            if self.status == Status::FilterEdit {
+                let x = layout[1].x + g::string_width(&tf(Msg::Filter, &[&self.filter.before_cursor()])) as u16;
+                if x < layout[1].right() {
+                    buf[(x, layout[1].y)].set_style(Style::default().add_modifier(Modifier::REVERSED));
+                }
+            }
         } else {
-            self.inner_widget.show(area, buf);
+            self.inner_widget.render(area, buf);
+            self.render_cost = start.elapsed();
+        }
+    }
+
+    fn get_menu(&self) -> Vec<(String, &'static str)> {
+        match self.status {
+            Status::Normal => self.inner_widget.get_menu(),
+            Status::FilterEdit => Self::get_menu_filter_edit(),
         }
     }
+
+    fn actions(&self) -> Vec<Action> {
+        match self.status {
+            Status::Normal if self.inner_widget.is_editing() => self.inner_widget.actions(),
+            Status::Normal => [Self::ACTIONS_NORMAL.as_slice(), &self.inner_widget.actions()].concat(),
+            Status::FilterEdit => [Self::ACTIONS_EDIT.as_slice(), &Self::ACTIONS_HISTORY, &TextInput::ACTIONS].concat(),
+        }
+    }
+
+    fn on_data(&mut self, data: &dyn Any) {
+        self.inner_widget.on_data(data)
+    }
+
+    fn set_api(&mut self, api: ClashApi) {
+        self.inner_widget.set_api(api)
+    }
+
+    fn set_filter(&mut self, filter: &Query) {
+        self.filter.set_text(filter.text());
+        self.error = None;
+        self.apply(filter);
+    }
+
+    // 正在编辑过滤条件时，所有的字符都作为输入
+    fn is_editing(&self) -> bool {
+        self.status == Status::FilterEdit || self.inner_widget.is_editing()
+    }
+
+    fn table_mut(&mut self) -> Option<&mut TableWidget> {
+        self.inner_widget.table_mut()
+    }
 }
@@ -0,0 +1,63 @@
+use crate::g;
+use crate::keymap::{self, Action};
+use crate::theme::theme;
+use crate::i18n::{t, Msg};
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Alignment, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Clear, Padding, Paragraph, Widget};
+
+// 在界面中间显示actions的所有按键，按分类分组
+pub fn render_help(actions: &[Action], area: Rect, buf: &mut Buffer) {
+    let theme = theme();
+    let help = keymap::help(actions);
+    let key_width = help
+        .iter()
+        .flat_map(|(_, items)| items.iter())
+        .map(|(keys, _)| g::string_width(keys))
+        .max()
+        .unwrap_or(0);
+
+    let mut lines = vec![];
+    for (title, items) in help {
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(title).add_modifier(Modifier::BOLD).fg(theme.header_fg));
+        for (keys, text) in items {
+            let padding = " ".repeat(key_width - g::string_width(&keys) + 2);
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                Span::styled(keys, Style::default().fg(theme.checked_fg)),
+                Span::raw(padding),
+                Span::styled(text, Style::default().fg(theme.row_fg)),
+            ]));
+        }
+    }
+
+    let width = lines.iter().map(Line::width).max().unwrap_or(0) as u16 + 4;
+    let height = lines.len() as u16 + 2;
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    Clear.render(popup, buf);
+    Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(format!(" {} ", t(Msg::Help)))
+                .title_alignment(Alignment::Center)
+                .title_bottom(Line::from(format!(" {} ", t(Msg::PressAnyKey))).alignment(Alignment::Center))
+                .padding(Padding::horizontal(1)),
+        )
+        .bg(theme.buffer_bg)
+        .fg(theme.row_fg)
+        .render(popup, buf);
+}
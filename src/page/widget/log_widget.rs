@@ -1,10 +1,13 @@
-use crate::g::COLOR;
+use crate::theme::theme;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::prelude::{Line, StatefulWidget, Style, Text};
 use ratatui::style::Stylize;
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Widget, Wrap};
-use std::borrow::Cow;
+use crate::g;
+use std::ops::Range;
+use crate::page::widget::{highlight, Query, Scroll};
+use crate::page::widget::query::Field;
 
 pub struct LogWidget {
     // 最大缓存行数
@@ -14,12 +17,18 @@ pub struct LogWidget {
 
     // 滚动条信息
     scroll_state: ScrollbarState,
+    // None表示跟随最新的日志
     scroll_pos: Option<usize>,
+    // 上次绘制时显示的第一行
+    last_pos: usize,
 
     cached_width: usize,
-    cached_lines: Vec<String>,
-    // 过滤字符串
-    filter: String,
+    // 折行后的每一行、其中需要高亮的位置和所属日志在lines中的位置
+    cached_lines: Vec<(String, Vec<Range<usize>>, usize)>,
+    // 上次绘制时能显示的行数，用于翻页
+    page_size: usize,
+    // 过滤条件
+    filter: Query,
 }
 
 impl LogWidget {
@@ -29,21 +38,54 @@ impl LogWidget {
             scroll_state: Default::default(),
             lines: Vec::new(),
             scroll_pos: None,
+            last_pos: 0,
 
             cached_width: usize::MAX,
             cached_lines: Vec::new(),
-            filter: String::new(),
+            page_size: 1,
+            filter: Query::default(),
         }
     }
 
-    pub fn set_filter(&mut self, filter: &str) {
-        if self.filter != filter {
-            self.filter = filter.to_string();
+    pub fn set_filter(&mut self, filter: &Query) {
+        if self.filter.text() != filter.text() {
+            self.filter = filter.clone();
             self.cached_width = usize::MAX;
             self.cached_lines.clear();
         }
     }
 
+    // 日志没有列，整行作为一个字段
+    fn matches(&self, line: &str) -> bool {
+        self.filter.matches(&[Field { name: "", text: line, number: None }])
+    }
+
+    // 折行后的每一行，在整行上查找匹配的位置再分到各行，跨行的匹配两边都能高亮
+    fn wrap(&self, index: usize, line: &str, width: usize) -> Vec<(String, Vec<Range<usize>>, usize)> {
+        let ranges = self.filter.highlights(&Field { name: "", text: line, number: None });
+        g::wrap_ranges(line, width)
+            .into_iter()
+            .map(|part| {
+                let highlights = ranges
+                    .iter()
+                    .filter(|x| x.start < part.end && x.end > part.start)
+                    .map(|x| x.start.max(part.start) - part.start..x.end.min(part.end) - part.start)
+                    .collect();
+                (line[part].to_string(), highlights, index)
+            })
+            .collect()
+    }
+
+    pub fn set_max(&mut self, max: usize) {
+        self.max = max;
+        if self.lines.len() > self.max {
+            let c = self.lines.len() - self.max;
+            self.lines.drain(0..c);
+            self.cached_lines.clear();
+            self.cached_width = usize::MAX;
+        }
+    }
+
     pub fn add_line(&mut self, line: String) {
         self.lines.push(line.clone());
         if self.lines.len() > self.max {
@@ -53,19 +95,16 @@ impl LogWidget {
             self.cached_lines.clear();
             self.cached_width = usize::MAX;
         } else if self.cached_width != usize::MAX {
-            let width = self.cached_width - 2;
-            if self.filter.is_empty() || line.contains(&self.filter) {
-                self.cached_lines.extend(
-                    textwrap::wrap(&line, width)
-                        .into_iter()
-                        .map(Cow::into_owned)
-                );
+            // cached_width已经去掉了边框，与render中折行的宽度相同
+            if self.matches(&line) {
+                let lines = self.wrap(self.lines.len() - 1, &line, self.cached_width);
+                self.cached_lines.extend(lines);
             }
         }
-        self.scroll_pos = None;
     }
 
     pub fn clear(&mut self) {
+        self.scroll_pos = None;
         self.cached_width = usize::MAX;
         self.cached_lines.clear();
         self.lines.clear();
@@ -74,43 +113,40 @@ impl LogWidget {
     pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
         let width = (area.width - 2) as usize;
         let height = (area.height - 2) as usize;
+        self.page_size = height.max(1);
 
         if width != self.cached_width {
             self.cached_width = width;
             self.cached_lines = self.lines.iter()
-                .filter(|l| {
-                    if self.filter.is_empty() {
-                        return true;
-                    }
-                    l.contains(&self.filter)
-                })
-                .flat_map(|x| {textwrap::wrap(x, width)})
-                .map(Cow::into_owned)
-                .collect::<Vec<String>>();
+                .enumerate()
+                .filter(|(_, l)| self.matches(l))
+                .flat_map(|(i, x)| self.wrap(i, x, width))
+                .collect::<Vec<_>>();
         }
 
 
         let total_line = self.cached_lines.len();
-        let scroll_max = if total_line > height { total_line - height } else { 0 };
+        let scroll_max = total_line.saturating_sub(height);
 
-        let mut i = self.scroll_pos.unwrap_or(scroll_max);
-        if i > scroll_max {
-            i = scroll_max;
+        let i = self.scroll_pos.unwrap_or(scroll_max).min(scroll_max);
+        if i == scroll_max {
+            self.scroll_pos = None;
         }
         self.scroll_state = self.scroll_state.content_length(scroll_max).position(i);
-        self.scroll_pos = Some(i);
+        self.last_pos = i;
 
         let lines: Vec<Line> = self.cached_lines
             .iter()
-            .skip(self.scroll_pos.unwrap())
-            .map(String::as_str)
-            .map(Line::from)
+            .skip(i)
+            .take(height)
+            .map(|(line, ranges, _)| highlight(line, ranges))
             .collect();
 
+        let theme = theme();
         let p = Paragraph::new(Text::from(lines))
-            .style(Style::default().fg(COLOR.row_fg))
+            .style(Style::default().fg(theme.row_fg))
             .wrap(Wrap{ trim: false })
-            .block(Block::default().borders(Borders::ALL).border_type(BorderType::Double).bg(COLOR.buffer_bg));
+            .block(Block::default().borders(Borders::ALL).border_type(BorderType::Double).bg(theme.buffer_bg));
 
         p.render(area, buf);
 
@@ -126,28 +162,25 @@ impl LogWidget {
 
     }
 
-    pub fn select_up(&mut self) {
-        self.scroll_state.prev();
-        self.scroll_pos = match self.scroll_pos {
-            Some(i) => {
-                if i > 0 {
-                    Some(i - 1)
-                } else {
-                    Some(0)
-                }
-            },
-            None => None,
-        };
+    // 当前的日志：上次绘制时最下面一行所属的日志，跟随最新的日志时就是最新的一条
+    pub fn current_line(&self) -> Option<&str> {
+        let end = (self.last_pos + self.page_size).min(self.cached_lines.len());
+        let (_, _, index) = self.cached_lines.get(end.checked_sub(1)?)?;
+        self.lines.get(*index).map(String::as_str)
     }
 
-    pub fn select_down(&mut self) {
-        self.scroll_state.next();
-        self.scroll_pos = match self.scroll_pos {
-            Some(i) => {
-                Some(i + 1)
-            },
-            None => None,
+    pub fn scroll(&mut self, scroll: Scroll) {
+        let i = self.scroll_pos.unwrap_or(self.last_pos);
+        self.scroll_pos = match scroll {
+            Scroll::Up => Some(i.saturating_sub(1)),
+            Scroll::Down => Some(i + 1),
+            Scroll::PageUp => Some(i.saturating_sub(self.page_size)),
+            Scroll::PageDown => Some(i + self.page_size),
+            Scroll::Top => Some(0),
+            // 回到末尾后跟随新的日志
+            Scroll::Bottom => None,
+            // 日志已经折行，不需要横向滚动
+            Scroll::Left | Scroll::Right => self.scroll_pos,
         };
     }
-
 }
\ No newline at end of file
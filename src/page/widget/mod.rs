@@ -1,8 +1,98 @@
 mod table_widget;
+mod table_row;
+mod query;
 mod log_widget;
+mod text_input;
 pub mod filter_widget;
+pub mod column_widget;
+pub mod detail_widget;
+pub mod help_widget;
+pub mod command_widget;
 
-pub(super) use filter_widget::FilterInnerWidget;
 pub(super) use log_widget::LogWidget;
-pub(super) use table_widget::TableWidget;
+pub(super) use table_widget::Click;
+pub use table_widget::TableWidget;
+pub use query::Query;
+pub(super) use table_row::{CellStyle, TableRow, Value};
 
+use std::ops::Range;
+use crossterm::event::MouseEventKind;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use crate::keymap::Action;
+use crate::theme::theme;
+
+// 表格和日志的滚动方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scroll {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    // 表格左右按列滚动
+    Left,
+    Right,
+}
+
+impl Scroll {
+    pub const ACTIONS: [Action; 8] = [
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::ScrollLeft,
+        Action::ScrollRight,
+    ];
+
+    pub fn from_action(action: Action) -> Option<Self> {
+        match action {
+            Action::Up => Some(Scroll::Up),
+            Action::Down => Some(Scroll::Down),
+            Action::PageUp => Some(Scroll::PageUp),
+            Action::PageDown => Some(Scroll::PageDown),
+            Action::Top => Some(Scroll::Top),
+            Action::Bottom => Some(Scroll::Bottom),
+            Action::ScrollLeft => Some(Scroll::Left),
+            Action::ScrollRight => Some(Scroll::Right),
+            _ => None,
+        }
+    }
+
+    // 鼠标滚轮
+    pub fn from_mouse(kind: MouseEventKind) -> Option<Self> {
+        match kind {
+            MouseEventKind::ScrollUp => Some(Scroll::Up),
+            MouseEventKind::ScrollDown => Some(Scroll::Down),
+            MouseEventKind::ScrollLeft => Some(Scroll::Left),
+            MouseEventKind::ScrollRight => Some(Scroll::Right),
+            _ => None,
+        }
+    }
+}
+
+
+// 把文字中与过滤条件匹配的部分高亮显示，ranges按顺序排列且不重叠
+pub fn highlight(text: &str, ranges: &[Range<usize>]) -> Line<'static> {
+    let theme = theme();
+    let style = Style::default()
+        .fg(theme.match_fg)
+        .bg(theme.match_bg)
+        .add_modifier(Modifier::BOLD);
+    let mut spans = vec![];
+    let mut pos = 0;
+    for range in ranges {
+        if range.start > pos {
+            spans.push(Span::raw(text[pos..range.start].to_string()));
+        }
+        spans.push(Span::styled(text[range.clone()].to_string(), style));
+        pos = range.end;
+    }
+    if pos < text.len() {
+        spans.push(Span::raw(text[pos..].to_string()));
+    }
+    Line::from(spans)
+}
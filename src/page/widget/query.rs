@@ -0,0 +1,477 @@
+use std::cmp::Ordering;
+use std::ops::Range;
+use regex::{Regex, RegexBuilder};
+use crate::i18n::{t, tf, Msg};
+
+// 数值的单位，比较时把条件中的数值换算到列的单位
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Plain,
+    Bytes,
+    Seconds,
+    Millis,
+}
+
+// 过滤时一行中的一列
+pub struct Field<'a> {
+    // 列名，条件中用"列名:"限定只匹配这一列
+    pub name: &'a str,
+    pub text: &'a str,
+    // 用于数值比较，没有数值的列不参与比较
+    pub number: Option<(f64, Unit)>,
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    // 文字也转换为正则，方便找到匹配的位置
+    Regex(Regex),
+    // 比较运算，数值和条件中的单位
+    Compare(Ordering, bool, f64, Option<Unit>),
+}
+
+#[derive(Debug, Clone)]
+struct Term {
+    negate: bool,
+    column: Option<String>,
+    matcher: Matcher,
+}
+
+// 过滤条件：空格分隔的条件同时满足(AND)，OR分隔的几组条件满足其中一组
+// 条件的写法：文字、"带空格的文字"、/正则/、/正则/i、列名:文字、列名>数值、-条件(取反)
+#[derive(Debug, Clone, Default)]
+pub struct Query {
+    text: String,
+    groups: Vec<Vec<Term>>,
+}
+
+impl Query {
+    // columns为可以使用的列名，为空时"xxx:"作为普通文字
+    pub fn parse(text: &str, columns: &[&str]) -> Result<Self, String> {
+        let mut parser = Parser { chars: text.chars().collect(), pos: 0, columns };
+        let mut groups = vec![vec![]];
+        while let Some(token) = parser.next_term()? {
+            match token {
+                Token::Or => groups.push(vec![]),
+                Token::And => {}
+                Token::Term(term) => groups.last_mut().unwrap().push(term),
+            }
+        }
+        groups.retain(|x| !x.is_empty());
+        Ok(Self { text: text.to_string(), groups })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn matches(&self, fields: &[Field]) -> bool {
+        self.is_empty() || self.groups.iter().any(|group| group.iter().all(|term| term.matches(fields)))
+    }
+
+    // 字段中与条件匹配的位置，按顺序排列且不重叠，用于高亮显示；取反的条件不高亮，数值比较满足时整个字段高亮
+    pub fn highlights(&self, field: &Field) -> Vec<Range<usize>> {
+        let mut ranges = self.groups
+            .iter()
+            .flatten()
+            .filter(|x| !x.negate && x.column.as_ref().is_none_or(|column| column.as_str() == field.name))
+            .flat_map(|x| match &x.matcher {
+                Matcher::Regex(regex) => regex.find_iter(field.text).map(|m| m.range()).filter(|r| !r.is_empty()).collect(),
+                matcher if matcher.matches(field) => std::iter::once(0..field.text.len()).collect(),
+                _ => vec![],
+            })
+            .collect::<Vec<_>>();
+        ranges.sort_by_key(|x| x.start);
+        let mut result: Vec<Range<usize>> = vec![];
+        for range in ranges {
+            match result.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => result.push(range),
+            }
+        }
+        result
+    }
+}
+
+impl Term {
+    fn matches(&self, fields: &[Field]) -> bool {
+        let found = fields
+            .iter()
+            .filter(|x| self.column.as_ref().is_none_or(|column| column.as_str() == x.name))
+            .any(|x| self.matcher.matches(x));
+        found != self.negate
+    }
+}
+
+impl Matcher {
+    fn matches(&self, field: &Field) -> bool {
+        match self {
+            Matcher::Regex(regex) => regex.is_match(field.text),
+            Matcher::Compare(ordering, or_equal, value, unit) => {
+                let Some((number, field_unit)) = field.number else {
+                    return false;
+                };
+                let value = match (unit, field_unit) {
+                    (None, _) => *value,
+                    (Some(Unit::Millis), Unit::Seconds) => *value / 1000.0,
+                    (Some(unit), field_unit) if *unit == field_unit => *value,
+                    _ => return false,
+                };
+                let result = number.total_cmp(&value);
+                result == *ordering || (*or_equal && result == Ordering::Equal)
+            }
+        }
+    }
+}
+
+enum Token {
+    And,
+    Or,
+    Term(Term),
+}
+
+struct Parser<'a> {
+    chars: Vec<char>,
+    pos: usize,
+    columns: &'a [&'a str],
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next_term(&mut self) -> Result<Option<Token>, String> {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+        if self.peek().is_none() {
+            return Ok(None);
+        }
+        let start = self.pos;
+        let word = self.word();
+        match word.as_str() {
+            "OR" | "|" => return Ok(Some(Token::Or)),
+            "AND" => return Ok(Some(Token::And)),
+            _ => self.pos = start,
+        }
+
+        let negate = self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|x| !x.is_whitespace());
+        if negate {
+            self.pos += 1;
+        }
+        let column = self.column()?;
+        // 比较运算符只能跟在列名后面，其他地方的>、<、=是普通文字
+        let operator = column.as_ref().and_then(|_| self.operator());
+        let matcher = match (operator, &column) {
+            (Some((ordering, or_equal)), Some(column)) => {
+                let value = self.word();
+                let (number, unit) = parse_number(&value).ok_or_else(|| tf(Msg::FilterBadNumber, &[&column, &value]))?;
+                Matcher::Compare(ordering, or_equal, number, unit)
+            }
+            _ => self.pattern()?,
+        };
+        Ok(Some(Token::Term(Term { negate, column, matcher })))
+    }
+
+    // 到下一个空白为止
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek().filter(|x| !x.is_whitespace()) {
+            word.push(c);
+            self.pos += 1;
+        }
+        word
+    }
+
+    // "列名:"或"列名"后面跟着比较运算符时为限定列的条件，列名以字母开头
+    fn column(&mut self) -> Result<Option<String>, String> {
+        let end = self.chars[self.pos..]
+            .iter()
+            .position(|x| !x.is_ascii_alphanumeric() && *x != '_')
+            .map_or(self.chars.len(), |x| self.pos + x);
+        let starts_with_letter = self.peek().is_some_and(|x| x.is_ascii_alphabetic());
+        if !starts_with_letter || !matches!(self.chars.get(end), Some(':' | '>' | '<' | '=')) {
+            return Ok(None);
+        }
+        let name = self.chars[self.pos..end].iter().collect::<String>();
+        let has_value = self.chars.get(end + 1).is_some_and(|x| !x.is_whitespace());
+        if !self.columns.contains(&name.as_str()) {
+            // 没有列的内容(比如日志)或者后面没有内容时，作为普通文字
+            if self.columns.is_empty() || !has_value {
+                return Ok(None);
+            }
+            return Err(tf(Msg::FilterUnknownColumn, &[&name, &self.columns.join(t(Msg::ListSeparator))]));
+        }
+        self.pos = end;
+        if self.peek() == Some(':') {
+            self.pos += 1;
+        }
+        if self.peek().is_none_or(char::is_whitespace) {
+            return Err(tf(Msg::FilterMissingValue, &[&name]));
+        }
+        Ok(Some(name))
+    }
+
+    fn operator(&mut self) -> Option<(Ordering, bool)> {
+        let result = match (self.peek(), self.chars.get(self.pos + 1)) {
+            (Some('>'), Some('=')) => (Ordering::Greater, true, 2),
+            (Some('<'), Some('=')) => (Ordering::Less, true, 2),
+            (Some('>'), _) => (Ordering::Greater, false, 1),
+            (Some('<'), _) => (Ordering::Less, false, 1),
+            (Some('='), _) => (Ordering::Equal, false, 1),
+            _ => return None,
+        };
+        self.pos += result.2;
+        Some((result.0, result.1))
+    }
+
+    // 文字、"带空格的文字"或者/正则/，正则后面加i不区分大小写
+    fn pattern(&mut self) -> Result<Matcher, String> {
+        match self.peek() {
+            Some('/') => {
+                self.pos += 1;
+                let pattern = self.until('/').ok_or_else(|| t(Msg::FilterUnclosedRegex).to_string())?;
+                let ignore_case = self.peek() == Some('i');
+                if ignore_case {
+                    self.pos += 1;
+                }
+                let regex = RegexBuilder::new(&pattern)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .map_err(|e| {
+                        // 错误信息有多行，过滤栏中只显示最后一行的原因
+                        let e = e.to_string();
+                        let reason = e.lines().last().unwrap_or_default().trim_start_matches("error: ").to_string();
+                        tf(Msg::FilterBadRegex, &[&pattern, &reason])
+                    })?;
+                Ok(Matcher::Regex(regex))
+            }
+            Some('"') => {
+                self.pos += 1;
+                let pattern = self.until('"').ok_or_else(|| t(Msg::FilterUnclosedQuote).to_string())?;
+                text_matcher(&pattern)
+            }
+            _ => text_matcher(&self.word()),
+        }
+    }
+
+    // 读到end为止，\end表示end本身
+    fn until(&mut self, end: char) -> Option<String> {
+        let mut result = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '\\' if self.peek() == Some(end) => {
+                    result.push(end);
+                    self.pos += 1;
+                }
+                c if c == end => return Some(result),
+                c => result.push(c),
+            }
+        }
+        None
+    }
+}
+
+// 与vim的smartcase相同，只有小写字母时不区分大小写
+fn text_matcher(pattern: &str) -> Result<Matcher, String> {
+    let ignore_case = !pattern.chars().any(char::is_uppercase);
+    RegexBuilder::new(&regex::escape(pattern))
+        .case_insensitive(ignore_case)
+        .build()
+        .map(Matcher::Regex)
+        .map_err(|e| e.to_string())
+}
+
+// 带单位的数值，比如"1MB"、"200ms"、"5min"，大小的单位按1024换算，时间统一换算为毫秒
+// 速度也使用大小的单位，不接受"MB/s"这样的写法
+fn parse_number(s: &str) -> Option<(f64, Option<Unit>)> {
+    let end = s.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(s.len());
+    let number = s[..end].parse::<f64>().ok()?;
+    let (scale, unit) = match s[end..].to_lowercase().as_str() {
+        "" => return Some((number, None)),
+        "b" => (1.0, Unit::Bytes),
+        "k" | "kb" | "kib" => (1024.0, Unit::Bytes),
+        "m" | "mb" | "mib" => (1024.0 * 1024.0, Unit::Bytes),
+        "g" | "gb" | "gib" => (1024.0 * 1024.0 * 1024.0, Unit::Bytes),
+        "t" | "tb" | "tib" => (1024.0 * 1024.0 * 1024.0 * 1024.0, Unit::Bytes),
+        "ms" => (1.0, Unit::Millis),
+        "s" => (1000.0, Unit::Millis),
+        "min" => (60.0 * 1000.0, Unit::Millis),
+        "h" => (3600.0 * 1000.0, Unit::Millis),
+        "d" => (86400.0 * 1000.0, Unit::Millis),
+        _ => return None,
+    };
+    Some((number * scale, Some(unit)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: [&str; 4] = ["host", "down", "delay", "duration"];
+
+    fn query(text: &str) -> Query {
+        Query::parse(text, &COLUMNS).unwrap()
+    }
+
+    fn text(name: &'static str, text: &'static str) -> Field<'static> {
+        Field { name, text, number: None }
+    }
+
+    fn number(name: &'static str, value: f64, unit: Unit) -> Field<'static> {
+        Field { name, text: "", number: Some((value, unit)) }
+    }
+
+    #[test]
+    fn smart_case() {
+        assert!(query("google").matches(&[text("host", "www.Google.com")]));
+        assert!(query("Google").matches(&[text("host", "www.Google.com")]));
+        assert!(!query("Google").matches(&[text("host", "www.google.com")]));
+    }
+
+    #[test]
+    fn regex() {
+        assert!(query("/^api\\./").matches(&[text("host", "api.example.com")]));
+        assert!(!query("/^api\\./").matches(&[text("host", "www.api.com")]));
+        assert!(!query("/TikTok/").matches(&[text("host", "tiktok.com")]));
+        assert!(query("/TikTok/i").matches(&[text("host", "tiktok.com")]));
+        // \/表示/本身
+        assert!(query("/a\\/b/").matches(&[text("host", "a/b")]));
+    }
+
+    #[test]
+    fn quoted_text() {
+        let fields = [text("host", "foo bar")];
+        assert!(query("\"foo bar\"").matches(&fields));
+        assert!(!query("\"foo  bar\"").matches(&fields));
+        // 引号中的正则字符按文字匹配
+        assert!(query("\"a.c\"").matches(&[text("host", "a.c")]));
+        assert!(!query("\"a.c\"").matches(&[text("host", "abc")]));
+    }
+
+    #[test]
+    fn column() {
+        let fields = [text("host", "google.com"), text("chain", "JP")];
+        assert!(query("host:google").matches(&fields));
+        assert!(!query("host:JP").matches(&fields));
+        assert!(query("JP").matches(&fields));
+    }
+
+    #[test]
+    fn unknown_column() {
+        assert!(Query::parse("chain:JP", &COLUMNS).is_err());
+        // 后面没有内容或者没有列(日志)时作为普通文字
+        assert!(query("chain: x").matches(&[text("host", "chain: x")]));
+        let log = Query::parse("level:info", &[]).unwrap();
+        assert!(log.matches(&[text("", "level:info started")]));
+    }
+
+    #[test]
+    fn compare_with_units() {
+        let down = [number("down", 2.0 * 1024.0 * 1024.0, Unit::Bytes)];
+        assert!(query("down>1MB").matches(&down));
+        assert!(query("down>1m").matches(&down));
+        assert!(!query("down>2MB").matches(&down));
+        assert!(query("down>=2MB").matches(&down));
+        assert!(query("down=2MiB").matches(&down));
+        assert!(query("down<3MB").matches(&down));
+
+        let duration = [number("duration", 300.0, Unit::Seconds)];
+        assert!(query("duration>=5min").matches(&duration));
+        assert!(!query("duration>5min").matches(&duration));
+        assert!(query("duration<1h").matches(&duration));
+        // 没有单位时按列的单位比较
+        assert!(query("duration=300").matches(&duration));
+
+        let delay = [number("delay", 150.0, Unit::Millis)];
+        assert!(query("delay<200").matches(&delay));
+        assert!(query("delay<0.2s").matches(&delay));
+    }
+
+    #[test]
+    fn compare_edge_cases() {
+        // 超时的延迟比任何数值都大，delay>200也包括超时
+        assert!(query("delay>200").matches(&[number("delay", f64::INFINITY, Unit::Millis)]));
+        // 时间单位不能用于没有单位的列，大小的单位也不能用于时间
+        assert!(!query("down>1ms").matches(&[number("down", 5.0, Unit::Plain)]));
+        assert!(!query("duration>1MB").matches(&[number("duration", 5.0, Unit::Seconds)]));
+        // 没有数值的列不参与比较
+        assert!(!query("delay>0").matches(&[text("delay", "")]));
+        // 速度也按大小比较，不接受/s
+        assert!(Query::parse("down>1MB/s", &COLUMNS).is_err());
+        assert!(Query::parse("down>1MBs", &COLUMNS).is_err());
+        assert!(Query::parse("down>fast", &COLUMNS).is_err());
+    }
+
+    #[test]
+    fn negate() {
+        let fields = [text("host", "google.com")];
+        assert!(!query("-google").matches(&fields));
+        assert!(query("-baidu").matches(&fields));
+        assert!(!query("-host:google").matches(&fields));
+        // 单独的-为普通文字
+        assert!(query("-").matches(&[text("host", "a-b")]));
+    }
+
+    #[test]
+    fn and_or() {
+        let fields = [text("host", "google.com"), text("chain", "JP")];
+        assert!(query("google JP").matches(&fields));
+        assert!(query("google AND JP").matches(&fields));
+        assert!(!query("google US").matches(&fields));
+        assert!(query("baidu OR JP").matches(&fields));
+        assert!(query("baidu | JP").matches(&fields));
+        assert!(!query("baidu | US").matches(&fields));
+        // AND优先于OR
+        assert!(query("baidu US OR google JP").matches(&fields));
+        assert!(!query("baidu OR google US").matches(&fields));
+    }
+
+    #[test]
+    fn empty() {
+        assert!(query("").is_empty());
+        assert!(query("  OR  ").is_empty());
+        assert!(query("").matches(&[text("host", "anything")]));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(Query::parse("/abc", &COLUMNS).is_err());
+        assert!(Query::parse("/(/", &COLUMNS).is_err());
+        assert!(Query::parse("\"abc", &COLUMNS).is_err());
+        assert!(Query::parse("host:", &COLUMNS).is_err());
+        assert!(Query::parse("host: x", &COLUMNS).is_err());
+    }
+
+    #[test]
+    fn highlights() {
+        let field = text("host", "google.com.google");
+        assert_eq!(query("google").highlights(&field), vec![0..6, 11..17]);
+        // 重叠的位置合并
+        assert_eq!(query("goo ogle").highlights(&field), vec![0..6, 11..17]);
+        // 取反和其他列的条件不高亮
+        assert!(query("-google").highlights(&field).is_empty());
+        assert_eq!(query("delay:x com").highlights(&field), vec![7..10]);
+        // 数值比较满足时整个字段高亮
+        let field = Field { name: "down", text: "2.0 MiB", number: Some((2.0 * 1024.0 * 1024.0, Unit::Bytes)) };
+        assert_eq!(query("down>1MB").highlights(&field), vec![0..7]);
+    }
+
+    #[test]
+    fn operator_without_column() {
+        assert!(query(">foo").matches(&[text("host", "a>foo")]));
+        assert!(!query(">foo").matches(&[text("host", "foo")]));
+        assert!(query("=").matches(&[text("host", "a=b")]));
+        assert!(!query("=").matches(&[text("host", "foo")]));
+        assert!(!query("<").matches(&[text("host", "foo")]));
+        assert!(!query("-<=").matches(&[text("host", "a<=b")]));
+        let log = Query::parse(">foo", &[]).unwrap();
+        assert!(!log.matches(&[text("", "foo")]));
+        assert!(log.matches(&[text("", "x >foo")]));
+    }
+}
@@ -0,0 +1,210 @@
+use std::cmp::Ordering;
+use humansize::{format_size, BINARY};
+use crate::i18n::{t, Msg};
+use crate::page::widget::query::Unit;
+
+// 单元格的值，显示时才格式化，排序和过滤按实际类型处理
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(u64),
+    // 字节数
+    Bytes(u64),
+    // 每秒的字节数
+    Rate(u64),
+    // 时长，单位秒
+    Duration(u64),
+    // 延迟，单位毫秒，None为超时
+    Latency(Option<u32>),
+    Flag(bool),
+    // 没有数据，比如没有测过速的节点
+    Empty,
+}
+
+impl Value {
+    // 测速的结果，没有测过时为空，0为超时
+    pub fn delay(delay: Option<i32>) -> Self {
+        match delay {
+            None => Value::Empty,
+            Some(x) if x <= 0 => Value::Latency(None),
+            Some(x) => Value::Latency(Some(x as u32)),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        match self {
+            Value::Text(x) => x.clone(),
+            Value::Number(x) => x.to_string(),
+            Value::Bytes(x) | Value::Rate(x) => format_size(*x, BINARY),
+            Value::Duration(x) => format_duration(*x),
+            Value::Latency(Some(x)) => format!("{x}ms"),
+            Value::Latency(None) => "-".into(),
+            Value::Flag(x) => if *x { "✓" } else { "" }.into(),
+            Value::Empty => String::new(),
+        }
+    }
+
+    // 排序时使用的数值，超时的延迟排在最后
+    fn number(&self) -> Option<f64> {
+        match self {
+            Value::Number(x) | Value::Bytes(x) | Value::Rate(x) | Value::Duration(x) => Some(*x as f64),
+            Value::Latency(Some(x)) => Some(*x as f64),
+            Value::Latency(None) => Some(f64::INFINITY),
+            Value::Flag(x) => Some(*x as u8 as f64),
+            Value::Text(_) | Value::Empty => None,
+        }
+    }
+
+    // 过滤条件中数值比较使用的数值和单位，超时的延迟比任何数值都大
+    pub fn quantity(&self) -> Option<(f64, Unit)> {
+        let unit = match self {
+            Value::Bytes(_) | Value::Rate(_) => Unit::Bytes,
+            Value::Duration(_) => Unit::Seconds,
+            Value::Latency(_) => Unit::Millis,
+            _ => Unit::Plain,
+        };
+        self.number().map(|x| (x, unit))
+    }
+
+    // 排序时的顺序，desc为倒序；数值排在文字前面，超时的延迟和没有数据的在两个方向上都排在最后
+    pub fn compare(&self, other: &Value, desc: bool) -> Ordering {
+        let rank = |x: &Value| match x {
+            Value::Latency(None) => 1,
+            Value::Empty => 2,
+            _ => 0,
+        };
+        let ordering = match (self, other) {
+            (Value::Text(a), Value::Text(b)) => a.cmp(b),
+            _ => match (self.number(), other.number()) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            },
+        };
+        rank(self).cmp(&rank(other)).then(if desc { ordering.reverse() } else { ordering })
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::Text(value)
+    }
+}
+
+// 单元格的样式，绘制时根据主题转换为颜色
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum CellStyle {
+    #[default]
+    Normal,
+    // 选中的项目，比如分组的当前节点
+    Checked,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableCell {
+    pub value: Value,
+    pub style: CellStyle,
+}
+
+impl From<Value> for TableCell {
+    fn from(value: Value) -> Self {
+        Self { value, style: CellStyle::Normal }
+    }
+}
+
+// 表格中的一行，key用于在数据刷新和排序后找到同一行
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableRow {
+    pub key: String,
+    pub cells: Vec<TableCell>,
+}
+
+impl TableRow {
+    pub fn new(key: impl Into<String>, values: Vec<Value>) -> Self {
+        Self {
+            key: key.into(),
+            cells: values.into_iter().map(TableCell::from).collect(),
+        }
+    }
+
+    // 整行使用同一个样式
+    pub fn with_style(mut self, style: CellStyle) -> Self {
+        for cell in &mut self.cells {
+            cell.style = style;
+        }
+        self
+    }
+}
+
+// 最多显示两个单位，比如"3分12秒"、"2天5小时"
+fn format_duration(seconds: u64) -> String {
+    if seconds < 10 {
+        return t(Msg::JustNow).to_string();
+    }
+    let units = [
+        (seconds / 86400, Msg::Days),
+        (seconds / 3600 % 24, Msg::Hours),
+        (seconds / 60 % 60, Msg::Minutes),
+        (seconds % 60, Msg::Seconds),
+    ];
+    let first = units.iter().position(|(n, _)| *n > 0).unwrap_or(units.len() - 1);
+    units[first..]
+        .iter()
+        .take(2)
+        .filter(|(n, _)| *n > 0)
+        .map(|(n, unit)| format!("{n}{}", t(*unit)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(mut values: Vec<Value>, desc: bool) -> Vec<Value> {
+        values.sort_by(|a, b| a.compare(b, desc));
+        values
+    }
+
+    #[test]
+    fn numbers() {
+        let values = vec![Value::Bytes(2048), Value::Bytes(10), Value::Bytes(300)];
+        assert_eq!(sorted(values.clone(), false), [Value::Bytes(10), Value::Bytes(300), Value::Bytes(2048)]);
+        assert_eq!(sorted(values, true), [Value::Bytes(2048), Value::Bytes(300), Value::Bytes(10)]);
+    }
+
+    #[test]
+    fn text() {
+        let values = vec![Value::from("b"), Value::from("a"), Value::from("c")];
+        assert_eq!(sorted(values.clone(), false), [Value::from("a"), Value::from("b"), Value::from("c")]);
+        assert_eq!(sorted(values, true), [Value::from("c"), Value::from("b"), Value::from("a")]);
+        assert_eq!(Value::Number(100).compare(&Value::from("a"), false), Ordering::Less);
+    }
+
+    #[test]
+    fn timeout_and_empty_last() {
+        let values = vec![
+            Value::Empty,
+            Value::Latency(Some(300)),
+            Value::Latency(None),
+            Value::Latency(Some(50)),
+        ];
+        let expected = [Value::Latency(Some(50)), Value::Latency(Some(300)), Value::Latency(None), Value::Empty];
+        assert_eq!(sorted(values.clone(), false), expected);
+        let expected = [Value::Latency(Some(300)), Value::Latency(Some(50)), Value::Latency(None), Value::Empty];
+        assert_eq!(sorted(values, true), expected);
+    }
+
+    #[test]
+    fn equal() {
+        assert_eq!(Value::Empty.compare(&Value::Empty, true), Ordering::Equal);
+        assert_eq!(Value::Latency(None).compare(&Value::Latency(None), false), Ordering::Equal);
+        assert_eq!(Value::from("a").compare(&Value::from("a"), true), Ordering::Equal);
+    }
+}
@@ -1,11 +1,19 @@
 use crate::g;
-use crate::g::COLOR;
+use crate::my_event::AppEvent;
+use crate::app_config::CONFIG;
+use crate::columns::ColumnConfig;
+use crate::page::widget::{highlight, CellStyle, Query, Scroll, TableRow};
+use crate::page::widget::query::Field;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use crate::theme::theme;
+use crate::i18n::{t, tf, Msg};
+use crate::keymap::{self, Action};
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Rect},
-    style::{palette::tailwind, Modifier, Style, Stylize as _}
-    ,
-    text::Text,
+    style::{Modifier, Style, Stylize as _},
     widgets::{
         Cell, HighlightSpacing, Row, Scrollbar, ScrollbarOrientation,
         ScrollbarState, StatefulWidget, Table, TableState,
@@ -16,41 +24,408 @@ pub struct TableWidget {
     table_state: TableState,
     scroll_state: ScrollbarState,
 
-    header: Vec<&'static str>,
-    data: Vec<Vec<String>>,
-    filter: String,
+    // 每一列的名称和表头，名称用于保存列设置
+    header: Vec<(&'static str, Msg)>,
+    // 列的显示顺序、是否隐藏、宽度，包含所有的列
+    layout: Vec<ColumnConfig>,
+    data: Vec<TableRow>,
+    // 与data一一对应的格式化结果，数据变化时才重新计算
+    cache: Vec<RowCache>,
+    // 每一列内容的最大显示宽度
+    natural: Vec<usize>,
+    filter: Query,
+    // 满足过滤条件的行在data中的位置，已排序，选中行是这里的下标
+    filtered: Vec<usize>,
+    // 排序的列和是否倒序，None时保持调用者给出的顺序
+    sort: Option<(usize, bool)>,
+    // 上次绘制时能显示的行数，用于翻页
+    page_size: usize,
+    // 上次绘制表格的区域，用于鼠标点击
+    area: Rect,
+    // 上次绘制时每一列在data中的位置、起始位置和宽度，用于点击表头排序
+    columns: Vec<(usize, u16, u16)>,
+    // 上次点击的行和时间，用于判断双击
+    last_click: Option<(usize, Instant)>,
+    // 多选时选择的行的key，以及上次选择或取消的行，用于选择一段
+    marked: HashSet<String>,
+    anchor: Option<String>,
+    // 横向滚动时跳过的未固定的列数，以及上次绘制时右边是否还有没显示的列
+    column_offset: usize,
+    more_right: bool,
+    // 自动宽度的列的最大宽度，配置变化时由set_max_column_width更新
+    max_column_width: usize,
+}
+
+// 一行格式化后的文字和显示宽度，绘制和过滤时使用
+struct RowCache {
+    texts: Vec<String>,
+    widths: Vec<usize>,
+}
+
+impl RowCache {
+    fn new(row: &TableRow) -> Self {
+        let texts = row.cells.iter().map(|x| x.value.text()).collect::<Vec<_>>();
+        let widths = texts.iter().map(|x| g::string_width(x)).collect();
+        Self { texts, widths }
+    }
+}
+
+// 压缩列宽时每列至少保留的宽度
+const MIN_COLUMN_WIDTH: usize = 6;
+// 两次点击同一行的间隔小于这个值时认为是双击
+const DOUBLE_CLICK: Duration = Duration::from_millis(500);
+
+// 鼠标点击或滚动表格的结果
+pub enum Click {
+    // 没有点中任何行
+    None,
+    // 选中了一行
+    Select,
+    // 双击的行的key
+    Double(String),
+    // 点击表头改变了排序
+    Sort,
+    // 滚轮滚动了表格
+    Scroll,
 }
 
 impl TableWidget {
-    pub fn new(header: Vec<&'static str>) -> Self {
+    pub fn new(header: Vec<(&'static str, Msg)>) -> Self {
         Self {
             table_state: Default::default(),
             scroll_state: Default::default(),
+            layout: header.iter().map(|(name, _)| ColumnConfig::new(name)).collect(),
             header,
             data: Vec::new(),
-            filter: String::new(),
+            cache: Vec::new(),
+            natural: Vec::new(),
+            filter: Query::default(),
+            filtered: Vec::new(),
+            sort: None,
+            page_size: 1,
+            area: Rect::default(),
+            columns: Vec::new(),
+            last_click: None,
+            marked: HashSet::new(),
+            anchor: None,
+            column_offset: 0,
+            more_right: false,
+            max_column_width: Self::max_width(CONFIG.read().unwrap().settings.table.max_column_width),
+        }
+    }
+
+    // 配置中的0表示不限制
+    fn max_width(width: u16) -> usize {
+        match width {
+            0 => usize::MAX,
+            x => x as usize,
         }
     }
 
-    pub fn set_filter(&mut self, filter: &str) {
-        if self.filter != filter {
-            self.filter = filter.to_string();
+    pub fn set_max_column_width(&mut self, width: u16) {
+        self.max_column_width = Self::max_width(width);
+    }
+
+    // 表格自己处理的按键：滚动、排序、多选和复制，对选择的行的操作由界面实现
+    pub const ACTIONS: [Action; 14] = [
+        Action::Up,
+        Action::Down,
+        Action::PageUp,
+        Action::PageDown,
+        Action::Top,
+        Action::Bottom,
+        Action::ScrollLeft,
+        Action::ScrollRight,
+        Action::Sort,
+        Action::SortReverse,
+        Action::Mark,
+        Action::MarkRange,
+        Action::MarkAll,
+        Action::Copy,
+    ];
+
+    // 处理表格的按键，返回需要发送的事件，不是表格的按键时返回None
+    pub fn on_key(&mut self, key_event: &KeyEvent) -> Option<AppEvent> {
+        let action = keymap::find(key_event, &Self::ACTIONS)?;
+        if let Some(scroll) = Scroll::from_action(action) {
+            self.scroll(scroll);
+            return Some(AppEvent::Draw);
+        }
+        match action {
+            Action::Sort | Action::SortReverse => {
+                self.sort(action);
+                Some(AppEvent::Draw)
+            }
+            _ => Some(self.mark(action)),
         }
     }
 
-    pub fn set_data(&mut self, data: Vec<Vec<String>>) {
-        if data.eq(&self.data) {
+    // 处理左键点击和滚轮，结果为Click::None时不需要重新绘制
+    pub fn on_mouse(&mut self, mouse_event: &MouseEvent) -> Click {
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => self.click(mouse_event.column, mouse_event.row),
+            kind => match Scroll::from_mouse(kind) {
+                Some(scroll) => {
+                    self.scroll(scroll);
+                    Click::Scroll
+                }
+                None => Click::None,
+            },
+        }
+    }
+
+    pub fn set_filter(&mut self, filter: &Query) {
+        if self.filter.text() != filter.text() {
+            self.filter = filter.clone();
+            self.keep_selection(Self::update_filtered);
+        }
+    }
+
+    // 所有列的名称，过滤条件中用于限定列
+    pub fn column_names(&self) -> Vec<&'static str> {
+        self.header.iter().map(|(name, _)| *name).collect()
+    }
+
+    fn filter_matches(&self, row: &TableRow, cache: &RowCache) -> bool {
+        let fields = self.header
+            .iter()
+            .zip(&row.cells)
+            .zip(&cache.texts)
+            .map(|(((name, _), cell), text)| Field { name, text, number: cell.value.quantity() })
+            .collect::<Vec<_>>();
+        self.filter.matches(&fields)
+    }
+
+    // 使用保存的列设置，其中没有的列按原来的顺序加在最后，已经不存在的列忽略
+    pub fn set_layout(&mut self, layout: &[ColumnConfig]) {
+        let mut result = layout
+            .iter()
+            .filter(|x| self.header.iter().any(|(name, _)| *name == x.name))
+            .cloned()
+            .collect::<Vec<_>>();
+        for (name, _) in &self.header {
+            if !result.iter().any(|x| x.name == *name) {
+                result.push(ColumnConfig::new(name));
+            }
+        }
+        // 至少显示一列
+        if result.iter().all(|x| x.hidden) {
+            result[0].hidden = false;
+        }
+        self.layout = result;
+    }
+
+    pub fn layout(&self) -> &[ColumnConfig] {
+        &self.layout
+    }
+
+    pub fn layout_mut(&mut self) -> &mut Vec<ColumnConfig> {
+        &mut self.layout
+    }
+
+    // 是否为默认的列设置
+    pub fn is_default_layout(&self) -> bool {
+        self.layout.iter().zip(&self.header).all(|(x, (name, _))| *x == ColumnConfig::new(name))
+    }
+
+    pub fn reset_layout(&mut self) {
+        self.layout = self.header.iter().map(|(name, _)| ColumnConfig::new(name)).collect();
+    }
+
+    pub fn column_title(&self, name: &str) -> &'static str {
+        self.header.iter().find(|(x, _)| *x == name).map_or("", |(_, title)| t(*title))
+    }
+
+    // 列内容的最大显示宽度，用于从自动宽度改为固定宽度
+    pub fn natural_width(&self, name: &str) -> u16 {
+        let Some(column) = self.header.iter().position(|(x, _)| *x == name) else {
+            return 0;
+        };
+        self.natural
+            .get(column)
+            .copied()
+            .unwrap_or(0)
+            .max(g::string_width(t(self.header[column].1))) as u16
+    }
+
+    // 显示的列在data中的位置，固定的列在前面
+    fn visible_columns(&self) -> Vec<usize> {
+        let pinned = self.layout.iter().filter(|x| !x.hidden && x.pinned);
+        let others = self.layout.iter().filter(|x| !x.hidden && !x.pinned);
+        pinned
+            .chain(others)
+            .filter_map(|x| self.header.iter().position(|(name, _)| *name == x.name))
+            .collect()
+    }
+
+    fn column_config(&self, column: usize) -> Option<&ColumnConfig> {
+        self.layout.iter().find(|x| x.name == self.header[column].0)
+    }
+
+    // Sort按显示顺序切换排序的列，最后一列之后恢复原来的顺序；SortReverse反转排序方向
+    pub fn sort(&mut self, action: Action) {
+        let visible = self.visible_columns();
+        let first = visible.first().copied().unwrap_or(0);
+        let sort = match (action, self.sort) {
+            (Action::SortReverse, Some((column, desc))) => Some((column, !desc)),
+            (Action::SortReverse, None) => Some((first, true)),
+            (_, None) => Some((first, false)),
+            (_, Some((column, _))) => match visible.iter().position(|x| *x == column) {
+                Some(i) => visible.get(i + 1).map(|x| (*x, false)),
+                None => Some((first, false)),
+            },
+        };
+        self.set_sort(sort);
+    }
+
+    fn set_sort(&mut self, sort: Option<(usize, bool)>) {
+        self.sort = sort;
+        self.keep_selection(Self::update_filtered);
+    }
+
+    // 重新计算filtered后按key保持原来选中的行，这一行不在了时保持选中的位置
+    fn keep_selection(&mut self, f: impl FnOnce(&mut Self)) {
+        let current = self.current_key().map(str::to_string);
+        f(self);
+        let pos = current.and_then(|key| self.filtered.iter().position(|i| self.data[*i].key == key));
+        if let Some(pos) = pos {
+            self.table_state.select(Some(pos));
+            self.update_scroll_state();
+        }
+    }
+
+    // 按key找到原来的行，内容没有变化的行沿用格式化的结果，所有的行都没有变化时不用重新过滤和排序
+    pub fn set_data(&mut self, data: Vec<TableRow>) {
+        let old = self.data
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.key.as_str(), i))
+            .collect::<HashMap<_, _>>();
+        let reuse = data
+            .iter()
+            .map(|row| old.get(row.key.as_str()).copied().filter(|i| self.data[*i] == *row))
+            .collect::<Vec<_>>();
+        if data.len() == self.data.len() && reuse.iter().enumerate().all(|(i, x)| *x == Some(i)) {
             return;
         }
 
-        self.data = data;
+        let mut old_cache = std::mem::take(&mut self.cache).into_iter().map(Some).collect::<Vec<_>>();
+        let cache = data
+            .iter()
+            .zip(reuse)
+            .map(|(row, i)| i.and_then(|i| old_cache[i].take()).unwrap_or_else(|| RowCache::new(row)))
+            .collect::<Vec<_>>();
+        self.natural = (0..self.header.len())
+            .map(|column| cache.iter().filter_map(|x| x.widths.get(column)).max().copied().unwrap_or(0))
+            .collect();
+        self.keep_selection(|x| {
+            x.data = data;
+            x.cache = cache;
+            x.update_filtered();
+        });
+        // 已经不存在的行不再选择
+        if !self.marked.is_empty() {
+            let keys = self.data.iter().map(|x| x.key.as_str()).collect::<HashSet<_>>();
+            self.marked.retain(|x| keys.contains(x.as_str()));
+        }
+    }
+
+    // Mark选择或取消当前行并移到下一行，MarkRange选择上次选择或取消的行到当前行，
+    // MarkAll选择所有满足过滤条件的行，已经全部选择时取消；返回需要发送的事件：状态栏的信息或者复制
+    pub fn mark(&mut self, action: Action) -> AppEvent {
+        let Some(current) = self.table_state.selected() else {
+            return AppEvent::Status(tf(Msg::MarkedCount, &[&self.marked.len()]));
+        };
+        let key = self.data[self.filtered[current]].key.clone();
+        match action {
+            Action::Mark => {
+                if !self.marked.remove(&key) {
+                    self.marked.insert(key.clone());
+                }
+                self.anchor = Some(key);
+                self.scroll(Scroll::Down);
+            }
+            Action::MarkRange => {
+                let anchor = self.anchor
+                    .as_ref()
+                    .and_then(|anchor| self.filtered.iter().position(|i| self.data[*i].key == *anchor))
+                    .unwrap_or(current);
+                for i in anchor.min(current)..=anchor.max(current) {
+                    self.marked.insert(self.data[self.filtered[i]].key.clone());
+                }
+                self.anchor = Some(key);
+            }
+            Action::MarkAll => {
+                let all = self.filtered.iter().all(|i| self.marked.contains(&self.data[*i].key));
+                if all {
+                    self.marked.clear();
+                } else {
+                    self.marked.extend(self.filtered.iter().map(|i| self.data[*i].key.clone()));
+                }
+            }
+            Action::Copy => {
+                let keys = self.selected_keys();
+                return AppEvent::Copy(self.export(&keys), tf(Msg::CopiedCount, &[&keys.len()]));
+            }
+            _ => {}
+        }
+        AppEvent::Status(tf(Msg::MarkedCount, &[&self.marked.len()]))
+    }
+
+    // 选择的行，按表格中的顺序
+    pub fn marked_keys(&self) -> Vec<String> {
+        self.data
+            .iter()
+            .filter(|x| self.marked.contains(&x.key))
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    // 操作的对象：有选择的行时为选择的行，否则为当前行
+    pub fn selected_keys(&self) -> Vec<String> {
+        match self.marked.is_empty() {
+            true => self.current_key().map(str::to_string).into_iter().collect(),
+            false => self.marked_keys(),
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.anchor = None;
+    }
+
+    // 以制表符分隔的文字导出行，第一行为表头，只包含显示的列
+    pub fn export(&self, keys: &[String]) -> String {
+        let columns = self.visible_columns();
+        let keys = keys.iter().collect::<HashSet<_>>();
+        let header = columns.iter().map(|i| t(self.header[*i].1)).collect::<Vec<_>>().join("\t");
+        let rows = self.data
+            .iter()
+            .zip(&self.cache)
+            .filter(|(row, _)| keys.contains(&row.key))
+            .map(|(_, cache)| columns.iter().map(|i| cache.texts[*i].as_str()).collect::<Vec<_>>().join("\t"));
+        std::iter::once(header).chain(rows).collect::<Vec<_>>().join("\n")
+    }
 
-        let mut len = self.data.len();
-        len = len.saturating_sub(1);
-        if self.table_state.selected().is_none() {
-            self.table_state.select_first();
+    fn update_filtered(&mut self) {
+        self.filtered = self.data.iter()
+            .zip(&self.cache)
+            .enumerate()
+            .filter(|(_, (row, cache))| self.filter.is_empty() || self.filter_matches(row, cache))
+            .map(|(i, _)| i)
+            .collect();
+        if let Some((column, desc)) = self.sort {
+            self.filtered.sort_by(|a, b| self.data[*a].cells[column].value.compare(&self.data[*b].cells[column].value, desc));
         }
-        self.scroll_state = self.scroll_state.content_length(len);
+
+        match self.table_state.selected() {
+            _ if self.filtered.is_empty() => self.table_state.select(None),
+            None => self.table_state.select_first(),
+            Some(i) if i >= self.filtered.len() => self.table_state.select(Some(self.filtered.len() - 1)),
+            _ => {},
+        }
+        self.scroll_state = self.scroll_state.content_length(self.filtered.len().saturating_sub(1));
+        self.update_scroll_state();
     }
 
     fn update_scroll_state(&mut self) {
@@ -59,134 +434,275 @@ impl TableWidget {
         };
     }
 
-    pub fn select_up(&mut self) -> Vec<String> {
-        if self.data.is_empty() {
-            return vec![];
+    fn current_index(&self) -> Option<usize> {
+        self.table_state.selected().and_then(|i| self.filtered.get(i)).copied()
+    }
+
+    // 移动选中行
+    pub fn scroll(&mut self, scroll: Scroll) {
+        // 左右按列滚动，右边的列都显示出来后不再滚动
+        match scroll {
+            Scroll::Left => self.column_offset = self.column_offset.saturating_sub(1),
+            Scroll::Right if self.more_right => self.column_offset += 1,
+            _ => {}
+        }
+        if self.filtered.is_empty() {
+            return;
         }
-        self.table_state.select_previous();
+        let last = self.filtered.len() - 1;
+        let i = self.table_state.selected().unwrap_or(0);
+        let i = match scroll {
+            Scroll::Up => i.saturating_sub(1),
+            Scroll::Down => i + 1,
+            Scroll::PageUp => i.saturating_sub(self.page_size),
+            Scroll::PageDown => i + self.page_size,
+            Scroll::Top => 0,
+            Scroll::Bottom => last,
+            Scroll::Left | Scroll::Right => return,
+        };
+        self.table_state.select(Some(i.min(last)));
         self.update_scroll_state();
-        self.data
-            .get(self.table_state.selected().unwrap())
-            .unwrap()
-            .clone()
     }
 
-    pub fn select_down(&mut self) -> Vec<String> {
-        if self.data.is_empty() {
-            return vec![];
+    // 选中点击的行，同一行连续点击两次为双击；点击表头按这一列排序，再次点击反转方向
+    fn click(&mut self, column: u16, row: u16) -> Click {
+        if row == self.area.y {
+            let Some(&(i, ..)) = self.columns.iter().find(|(_, x, w)| column >= *x && column < x + w) else {
+                return Click::None;
+            };
+            let desc = matches!(self.sort, Some((c, false)) if c == i);
+            self.set_sort(Some((i, desc)));
+            return Click::Sort;
         }
-        self.table_state.select_next();
-        self.update_scroll_state();
-        let mut i = self.table_state.selected().unwrap();
-        if i >= self.data.len() {
-            i = self.data.len() - 1;
+        // 第一行是表头
+        let rows = Rect { y: self.area.y + 1, height: self.area.height.saturating_sub(1), ..self.area };
+        if !rows.contains((column, row).into()) {
+            return Click::None;
+        }
+        let i = self.table_state.offset() + (row - rows.y) as usize;
+        if i >= self.filtered.len() {
+            return Click::None;
         }
         self.table_state.select(Some(i));
-        self.data.get(i).unwrap().clone()
+        self.update_scroll_state();
+        let key = self.current_key().unwrap_or_default().to_string();
+        let now = Instant::now();
+        match self.last_click.replace((i, now)) {
+            Some((last, time)) if last == i && now - time < DOUBLE_CLICK => {
+                self.last_click = None;
+                Click::Double(key)
+            }
+            _ => Click::Select,
+        }
     }
 
-    pub fn select<F: Fn(&Vec<String>) -> bool>(&mut self, f: F) {
-        for (i, row) in self.data.iter().enumerate() {
-            if f(row) {
-                self.table_state.select(Some(i));
-                self.update_scroll_state();
-                return;
-            }
+    // 选中key对应的行，找不到时选中第一行
+    pub fn select_key(&mut self, key: &str) {
+        let pos = self.filtered.iter().position(|i| self.data[*i].key == key);
+        match pos {
+            Some(pos) => self.table_state.select(Some(pos)),
+            None => self.table_state.select_first(),
         }
-        self.table_state.select_first();
         self.update_scroll_state();
     }
 
-    pub fn current_row(&self) -> Option<Vec<String>> {
-        match self.table_state.selected() {
-            None => None,
-            Some(i) => self.data.get(i).cloned(),
+    pub fn current_key(&self) -> Option<&str> {
+        self.current_index().and_then(|i| self.data.get(i)).map(|x| x.key.as_str())
+    }
+
+    // 表头，排序的列后面加上方向
+    fn header_titles(&self) -> Vec<String> {
+        self.header
+            .iter()
+            .enumerate()
+            .map(|(i, (_, x))| match self.sort {
+                Some((column, desc)) if column == i => format!("{}{}", t(*x), if desc { "▼" } else { "▲" }),
+                _ => t(*x).to_string(),
+            })
+            .collect()
+    }
+
+    // columns中每一列内容的显示宽度，总宽度超出width时从最宽的列开始压缩，被压缩的列显示时截断；
+    // 固定宽度和固定在前面的列不压缩，自动宽度的列不超过设置的最大宽度
+    // natural为每一列(包括没有显示的列)不压缩时的宽度
+    fn column_widths(&self, natural: &[usize], columns: &[usize], width: u16) -> Vec<usize> {
+        let natural = columns.iter().map(|i| natural[*i]).collect::<Vec<_>>();
+        let fixed = columns
+            .iter()
+            .zip(&natural)
+            .map(|(i, natural)| match self.column_config(*i) {
+                Some(ColumnConfig { width: Some(width), .. }) => Some(*width as usize),
+                Some(ColumnConfig { pinned: true, .. }) => Some(*natural),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        // 列间距1，除最后一列外每列两边留空格
+        let available = (width as usize).saturating_sub(3 * columns.len().saturating_sub(1));
+        let widths = |cap: usize| {
+            natural
+                .iter()
+                .zip(&fixed)
+                .map(move |(natural, fixed)| fixed.unwrap_or((*natural).min(cap)))
+        };
+        // 总宽度随cap单调增加，二分查找能放下的最大的cap，都放不下时为MIN_COLUMN_WIDTH
+        let max = natural.iter().copied().max().unwrap_or(0);
+        let (mut low, mut high) = (MIN_COLUMN_WIDTH.min(max), max);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+            match widths(mid).sum::<usize>() <= available {
+                true => low = mid,
+                false => high = mid - 1,
+            }
         }
+        widths(low).collect()
+    }
+
+    // 从横向滚动的位置开始，能完整显示的列和宽度；固定的列总是显示，至少显示一列未固定的列
+    fn fit_columns(&mut self, width: u16) -> (Vec<usize>, Vec<usize>) {
+        let visible = self.visible_columns();
+        let pinned = self.layout.iter().filter(|x| !x.hidden && x.pinned).count().min(visible.len());
+        self.column_offset = self.column_offset.min(visible.len().saturating_sub(pinned + 1));
+        let mut columns = [&visible[..pinned], &visible[pinned + self.column_offset..]].concat();
+        let titles = self.header_titles();
+        let natural = titles
+            .iter()
+            .enumerate()
+            .map(|(i, title)| self.natural.get(i).copied().unwrap_or(0).max(g::string_width(title)).min(self.max_column_width))
+            .collect::<Vec<_>>();
+        let mut widths = self.column_widths(&natural, &columns, width);
+        let too_wide = |widths: &[usize]| widths.iter().sum::<usize>() + 3 * widths.len().saturating_sub(1) > width as usize;
+        while columns.len() > pinned + 1 && too_wide(&widths) {
+            columns.pop();
+            widths = self.column_widths(&natural, &columns, width);
+        }
+        self.more_right = self.column_offset + columns.len() < visible.len();
+        // 最后一列使用剩余的全部宽度
+        let available = (width as usize).saturating_sub(3 * columns.len().saturating_sub(1));
+        let others = widths.iter().rev().skip(1).sum::<usize>();
+        if let Some(last) = widths.last_mut() {
+            *last = available.saturating_sub(others);
+        }
+        (columns, widths)
     }
 
     pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let theme = theme();
         let header_style = Style::default()
-            .fg(COLOR.header_fg)
-            .bg(COLOR.header_bg);
+            .fg(theme.header_fg)
+            .bg(theme.header_bg);
         let selected_style = Style::default()
             .add_modifier(Modifier::REVERSED)
-            .fg(COLOR.selected_style_fg);
-        let header = self
-            .header
+            .fg(theme.selected_fg);
+
+        let mut table_area = area;
+        table_area.width -= 1;
+        let (columns, widths) = self.fit_columns(table_area.width);
+        let titles = self.header_titles();
+        let header = columns
             .iter()
-            .map(|s| Cell::from(s as &str))
+            .zip(&widths)
+            .map(|(i, width)| Cell::from(g::truncate(&titles[*i], *width).into_owned()))
             .collect::<Row>()
             .style(header_style)
             .height(1);
 
-        let has_selected = if self.header.len() >= 3 {
-            self.header[2] == "选中"
-        } else { false };
-
-        let rows = self.data.iter()
-            .filter(|i| {
-                if self.filter.is_empty() {
-                    return true;
-                }
-                for item in *i {
-                    if item.contains(&self.filter) {
-                        return true;
-                    }
-                }
-                false
-            })
+        // 只为能显示的行生成Row，保持选中的行可见，行数减少时不在下面留空
+        let height = (table_area.height as usize).saturating_sub(1).max(1);
+        let mut offset = self.table_state.offset().min(self.filtered.len().saturating_sub(height));
+        if let Some(selected) = self.table_state.selected() {
+            offset = offset.clamp((selected + 1).saturating_sub(height), selected);
+        }
+        let end = (offset + height).min(self.filtered.len());
+        let rows = self.filtered[offset..end].iter()
+            .map(|i| (&self.data[*i], &self.cache[*i].texts))
             .enumerate()
-            .map(|(i, data)| {
-                let bg_color = match i % 2 {
-                    0 => COLOR.normal_row_color,
-                    _ => COLOR.alt_row_color,
-                };
-                let fg_color = match has_selected && !data[2].is_empty() {
-                    true => tailwind::GREEN.c500,
-                    false => COLOR.row_fg,
+            .map(|(i, (data, text))| {
+                let i = offset + i;
+                let marked = self.marked.contains(&data.key);
+                let row_style = match i % 2 {
+                    _ if marked => Style::new().bg(theme.marked_bg).add_modifier(Modifier::BOLD),
+                    0 => Style::new().bg(theme.normal_row_bg),
+                    _ => Style::new().bg(theme.alt_row_bg),
                 };
-                data.iter()
-                    .map(|str| Cell::from(Text::from(str as &str)))
+                columns
+                    .iter()
+                    .zip(&widths)
+                    .map(|(column, width)| {
+                        let fg_color = match data.cells[*column].style {
+                            CellStyle::Normal => theme.row_fg,
+                            CellStyle::Checked => theme.checked_fg,
+                        };
+                        let (name, _) = self.header[*column];
+                        let field = Field { name, text: &text[*column], number: data.cells[*column].value.quantity() };
+                        let shown = g::truncate(&text[*column], *width);
+                        // 截断时省略号不高亮，只保留显示出来的部分
+                        let visible = match shown.len() == text[*column].len() {
+                            true => shown.len(),
+                            false => shown.len().saturating_sub(g::ELLIPSIS.len()),
+                        };
+                        let ranges = self.filter
+                            .highlights(&field)
+                            .into_iter()
+                            .filter(|x| x.start < visible)
+                            .map(|x| x.start..x.end.min(visible))
+                            .collect::<Vec<_>>();
+                        Cell::from(highlight(&shown, &ranges)).fg(fg_color)
+                    })
                     .collect::<Row>()
-                    .style(Style::new().fg(fg_color).bg(bg_color))
+                    .style(row_style)
                     .height(1u16)
             });
 
-        // let block = Block::bordered()
-        //     .title(Title::from(&self.title as &str).alignment(Alignment::Center))
-        //     .border_set(border::DOUBLE);
+        // 与ratatui相同，列间距为1
+        let mut x = table_area.x;
+        self.columns = columns
+            .iter()
+            .zip(&widths)
+            .enumerate()
+            .map(|(i, (column, width))| {
+                let width = match i == widths.len() - 1 {
+                    true => table_area.right().saturating_sub(x),
+                    false => *width as u16 + 2,
+                };
+                let result = (*column, x, width);
+                x = x.saturating_add(width + 1);
+                result
+            })
+            .collect();
 
-        let mut widths = Vec::new();
-        for (i, header) in self.header.iter().enumerate() {
-            let width = self
-                .data
-                .iter()
-                .map(|row| g::string_width(&row[i]))
-                .max()
-                .unwrap_or(0) as u16;
-            let header_width = g::string_width(header) as u16;
-            let width = if width > header_width {
-                width
-            } else {
-                header_width
-            } + 2;
-            if i == header.len() - 1 {
-                widths.push(Constraint::Fill(1));
-            } else {
-                widths.push(Constraint::Length(width));
-            }
-        }
+        // 最后一列填满剩余的宽度，其余的列两边各留一个空格
+        let widths = widths
+            .iter()
+            .enumerate()
+            .map(|(i, width)| match i == columns.len() - 1 {
+                true => Constraint::Fill(1),
+                false => Constraint::Length(*width as u16 + 2),
+            })
+            .collect::<Vec<_>>();
 
         let t = Table::new(rows, widths)
             // .block(block)
             .header(header)
             .row_highlight_style(selected_style)
-            .bg(COLOR.buffer_bg)
+            .bg(theme.buffer_bg)
             .highlight_spacing(HighlightSpacing::Always);
 
-        let mut table_area = area;
-        table_area.width -= 1;
-        StatefulWidget::render(t, table_area, buf, &mut self.table_state);
+        self.page_size = height;
+        self.area = table_area;
+        *self.table_state.offset_mut() = offset;
+        let mut state = TableState::default().with_selected(self.table_state.selected().map(|x| x - offset));
+        StatefulWidget::render(t, table_area, buf, &mut state);
+
+        // 表头两端提示左右还有没显示的列
+        let pinned = self.layout.iter().filter(|x| !x.hidden && x.pinned).count();
+        if self.column_offset > 0 && !table_area.is_empty() {
+            if let Some((_, x, _)) = self.columns.get(pinned) {
+                buf[(*x, table_area.y)].set_symbol("◀");
+            }
+        }
+        if self.more_right && !table_area.is_empty() {
+            buf[(table_area.right() - 1, table_area.y)].set_symbol("▶");
+        }
 
         StatefulWidget::render(
             Scrollbar::default()
@@ -199,3 +715,110 @@ impl TableWidget {
         );
     }
 }
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page::widget::Value;
+
+    // 三列的内容宽度分别为10、20、30，都比表头宽
+    fn table(rows: usize) -> TableWidget {
+        let mut table = TableWidget::new(vec![("a", Msg::Name), ("b", Msg::Delay), ("c", Msg::Current)]);
+        let data = (0..rows)
+            .map(|i| {
+                let values = [10, 20, 30].map(|width| Value::from(format!("{i:0width$}")));
+                TableRow::new(i.to_string(), values.into())
+            })
+            .collect();
+        table.set_data(data);
+        table
+    }
+
+    fn render(table: &mut TableWidget, width: u16, height: u16) {
+        let area = Rect::new(0, 0, width, height);
+        table.render(area, &mut Buffer::empty(area));
+    }
+
+    #[test]
+    fn natural_widths() {
+        // 能放下时不压缩，最后一列使用剩余的宽度
+        assert_eq!(table(3).fit_columns(100), (vec![0, 1, 2], vec![10, 20, 64]));
+    }
+
+    #[test]
+    fn shrink_to_fit() {
+        // 可用宽度40，从最宽的列开始压缩到15
+        assert_eq!(table(3).fit_columns(46), (vec![0, 1, 2], vec![10, 15, 15]));
+        assert_eq!(table(3).fit_columns(45), (vec![0, 1, 2], vec![10, 14, 15]));
+        // 压缩到最小宽度还放不下时去掉后面的列，剩下的列重新压缩
+        assert_eq!(table(3).fit_columns(20), (vec![0, 1], vec![8, 9]));
+    }
+
+    #[test]
+    fn max_column_width() {
+        let mut table = table(3);
+        table.set_max_column_width(12);
+        assert_eq!(table.fit_columns(100), (vec![0, 1, 2], vec![10, 12, 72]));
+    }
+
+    #[test]
+    fn pinned_and_fixed() {
+        let mut table = table(3);
+        let mut pinned = ColumnConfig::new("c");
+        pinned.pinned = true;
+        let mut fixed = ColumnConfig::new("b");
+        fixed.width = Some(8);
+        table.set_layout(&[ColumnConfig::new("a"), fixed, pinned]);
+        // 固定的列在最前面且不压缩，固定宽度的列保持宽度，只压缩自动宽度的列
+        assert_eq!(table.fit_columns(50), (vec![2, 0, 1], vec![30, 6, 8]));
+        // 放不下时去掉后面的列，横向滚动时固定的列始终显示
+        assert_eq!(table.fit_columns(45), (vec![2, 0], vec![30, 12]));
+        assert!(table.more_right);
+        table.scroll(Scroll::Right);
+        assert_eq!(table.fit_columns(45), (vec![2, 1], vec![30, 12]));
+        assert!(!table.more_right);
+        // 右边的列都显示出来后不再滚动
+        table.scroll(Scroll::Right);
+        assert_eq!(table.fit_columns(45).0, [2, 1]);
+        // 向左滚动回到第一列
+        table.scroll(Scroll::Left);
+        assert_eq!(table.fit_columns(50).0, [2, 0, 1]);
+    }
+
+    #[test]
+    fn selected_visible_after_resize() {
+        let mut table = table(100);
+        render(&mut table, 100, 50);
+        table.scroll(Scroll::Bottom);
+        render(&mut table, 100, 50);
+        assert_eq!(table.table_state.offset(), 51);
+        // 变矮后选中的行仍然可见
+        render(&mut table, 100, 12);
+        assert_eq!(table.table_state.selected(), Some(99));
+        assert_eq!(table.table_state.offset(), 89);
+        // 选中开头后变高，不在下面留空
+        table.scroll(Scroll::Top);
+        render(&mut table, 100, 12);
+        assert_eq!(table.table_state.offset(), 0);
+        table.scroll(Scroll::Bottom);
+        render(&mut table, 100, 12);
+        render(&mut table, 100, 200);
+        assert_eq!(table.table_state.offset(), 0);
+    }
+
+    #[test]
+    fn offset_after_rows_removed() {
+        let mut table = table(100);
+        table.scroll(Scroll::Bottom);
+        render(&mut table, 100, 12);
+        assert_eq!(table.table_state.offset(), 89);
+        // 行数减少后选中最后一行，显示的范围跟着移动
+        let rows = table.data[..20].to_vec();
+        table.set_data(rows);
+        render(&mut table, 100, 12);
+        assert_eq!(table.table_state.selected(), Some(19));
+        assert_eq!(table.table_state.offset(), 9);
+    }
+}
@@ -0,0 +1,113 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::keymap::{self, Action};
+
+// 单行的文字输入，支持光标移动、按词删除和粘贴
+#[derive(Debug, Default)]
+pub struct TextInput {
+    text: String,
+    // 光标在text中的字节位置，总是在字形簇的边界上
+    cursor: usize,
+}
+
+impl TextInput {
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    // 光标前面的内容，用于计算光标显示的位置
+    pub fn before_cursor(&self) -> &str {
+        &self.text[..self.cursor]
+    }
+
+    // 替换全部内容，光标移到末尾
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+    }
+
+    // 在光标处插入，粘贴的多行内容合并为一行
+    pub fn insert(&mut self, text: &str) {
+        let text = text.trim_end_matches(['\r', '\n']).replace(['\r', '\n', '\t'], " ");
+        self.text.insert_str(self.cursor, &text);
+        self.cursor += text.len();
+    }
+
+    // 编辑按键，按键由keymap设置；历史由使用者处理
+    pub const ACTIONS: [Action; 9] = [
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::CursorHome,
+        Action::CursorEnd,
+        Action::DeleteBack,
+        Action::DeleteForward,
+        Action::DeleteWord,
+        Action::DeleteToStart,
+        Action::DeleteToEnd,
+    ];
+
+    // 处理编辑按键和输入的字符，都不是时返回false
+    pub fn on_key(&mut self, key_event: &KeyEvent) -> bool {
+        let Some(action) = keymap::find(key_event, &Self::ACTIONS) else {
+            return match key_event.code {
+                KeyCode::Char(_) if key_event.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => false,
+                KeyCode::Char(c) => {
+                    self.insert(c.encode_utf8(&mut [0; 4]));
+                    true
+                }
+                _ => false,
+            };
+        };
+        match action {
+            Action::CursorLeft => self.cursor = self.prev(),
+            Action::CursorRight => self.cursor = self.next(),
+            Action::CursorHome => self.cursor = 0,
+            Action::CursorEnd => self.cursor = self.text.len(),
+            Action::DeleteBack => {
+                let start = self.prev();
+                self.text.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            Action::DeleteForward => {
+                let end = self.next();
+                self.text.drain(self.cursor..end);
+            }
+            Action::DeleteWord => {
+                let start = self.word_start();
+                self.text.drain(start..self.cursor);
+                self.cursor = start;
+            }
+            Action::DeleteToStart => {
+                self.text.drain(..self.cursor);
+                self.cursor = 0;
+            }
+            Action::DeleteToEnd => self.text.truncate(self.cursor),
+            _ => return false,
+        }
+        true
+    }
+
+    // 光标前一个字形簇的位置
+    fn prev(&self) -> usize {
+        self.text[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next(&self) -> usize {
+        self.text[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map_or(self.cursor, |x| self.cursor + x.len())
+    }
+
+    // 与shell的Ctrl-W相同，删除光标前的空白和一个词
+    fn word_start(&self) -> usize {
+        let before = self.text[..self.cursor].trim_end();
+        before
+            .rfind(char::is_whitespace)
+            .map_or(0, |i| i + before[i..].chars().next().map_or(0, char::len_utf8))
+    }
+}
+
@@ -0,0 +1,227 @@
+use std::env;
+use std::str::FromStr;
+use std::sync::{LazyLock, RwLock};
+use indexmap::IndexMap;
+use ratatui::style::palette::tailwind;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use crate::i18n::{tf, Msg};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub buffer_bg: Color,
+    pub header_bg: Color,
+    pub header_fg: Color,
+    pub row_fg: Color,
+    pub selected_fg: Color,
+    pub normal_row_bg: Color,
+    pub alt_row_bg: Color,
+    // 分组中当前选中的节点
+    pub checked_fg: Color,
+    // 多选时选择的行
+    pub marked_bg: Color,
+    // 最下面一行的状态栏和按键说明
+    pub status_bg: Color,
+    pub status_fg: Color,
+    pub menu_key_fg: Color,
+    pub menu_fg: Color,
+    // 正在编辑的过滤条件
+    pub filter_edit_fg: Color,
+    // 与过滤条件匹配的文字
+    pub match_fg: Color,
+    pub match_bg: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Self {
+        Self {
+            buffer_bg: tailwind::SLATE.c950,
+            header_bg: tailwind::BLUE.c900,
+            header_fg: tailwind::SLATE.c200,
+            row_fg: tailwind::SLATE.c200,
+            selected_fg: tailwind::BLUE.c400,
+            normal_row_bg: tailwind::SLATE.c950,
+            alt_row_bg: tailwind::SLATE.c900,
+            checked_fg: tailwind::GREEN.c500,
+            marked_bg: tailwind::INDIGO.c900,
+            status_bg: tailwind::SLATE.c200,
+            status_fg: tailwind::BLACK,
+            menu_key_fg: tailwind::RED.c600,
+            menu_fg: tailwind::BLACK,
+            filter_edit_fg: tailwind::GREEN.c600,
+            match_fg: tailwind::BLACK,
+            match_bg: tailwind::YELLOW.c400,
+        }
+    }
+
+    pub fn light() -> Self {
+        Self {
+            buffer_bg: tailwind::SLATE.c50,
+            header_bg: tailwind::BLUE.c200,
+            header_fg: tailwind::SLATE.c900,
+            row_fg: tailwind::SLATE.c900,
+            selected_fg: tailwind::BLUE.c600,
+            normal_row_bg: tailwind::SLATE.c50,
+            alt_row_bg: tailwind::SLATE.c200,
+            checked_fg: tailwind::GREEN.c700,
+            marked_bg: tailwind::INDIGO.c100,
+            status_bg: tailwind::SLATE.c700,
+            status_fg: tailwind::SLATE.c50,
+            menu_key_fg: tailwind::RED.c300,
+            menu_fg: tailwind::SLATE.c50,
+            filter_edit_fg: tailwind::GREEN.c300,
+            match_fg: tailwind::BLACK,
+            match_bg: tailwind::YELLOW.c300,
+        }
+    }
+
+    // 只使用16色，用于不支持真彩色的终端
+    pub fn basic() -> Self {
+        Self {
+            buffer_bg: Color::Black,
+            header_bg: Color::Blue,
+            header_fg: Color::White,
+            row_fg: Color::Gray,
+            selected_fg: Color::LightBlue,
+            normal_row_bg: Color::Black,
+            alt_row_bg: Color::Black,
+            checked_fg: Color::LightGreen,
+            marked_bg: Color::DarkGray,
+            status_bg: Color::Gray,
+            status_fg: Color::Black,
+            menu_key_fg: Color::Red,
+            menu_fg: Color::Black,
+            filter_edit_fg: Color::Green,
+            match_fg: Color::Black,
+            match_bg: Color::Yellow,
+        }
+    }
+
+    // NO_COLOR时使用终端默认颜色，选中行靠反色区分
+    pub fn no_color() -> Self {
+        Self {
+            buffer_bg: Color::Reset,
+            header_bg: Color::Reset,
+            header_fg: Color::Reset,
+            row_fg: Color::Reset,
+            selected_fg: Color::Reset,
+            normal_row_bg: Color::Reset,
+            alt_row_bg: Color::Reset,
+            checked_fg: Color::Reset,
+            marked_bg: Color::Reset,
+            status_bg: Color::Reset,
+            status_fg: Color::Reset,
+            menu_key_fg: Color::Reset,
+            menu_fg: Color::Reset,
+            filter_edit_fg: Color::Reset,
+            match_fg: Color::Reset,
+            match_bg: Color::Reset,
+        }
+    }
+
+    fn builtin(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "basic" => Some(Self::basic()),
+            _ => None,
+        }
+    }
+}
+
+// 配置文件中的自定义主题，未设置的颜色取自base
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(flatten)]
+    pub colors: IndexMap<String, String>,
+}
+
+pub fn theme() -> Theme {
+    *THEME.read().unwrap()
+}
+
+pub fn set_theme(theme: Theme) {
+    *THEME.write().unwrap() = theme;
+}
+
+static THEME: LazyLock<RwLock<Theme>> = LazyLock::new(|| RwLock::new(Theme::default()));
+
+// 终端是否支持真彩色
+fn support_true_color() -> bool {
+    // 大部分256色终端的TERM也含有256color，只有COLORTERM能说明支持真彩色
+    env::var("COLORTERM").is_ok_and(|x| x == "truecolor" || x == "24bit")
+}
+
+// 根据名称得到主题，找不到或颜色写错时加入warnings
+pub fn resolve(name: &str, themes: &IndexMap<String, ThemeConfig>, warnings: &mut Vec<String>) -> Theme {
+    if env::var_os("NO_COLOR").is_some_and(|x| !x.is_empty()) {
+        return Theme::no_color();
+    }
+    // auto根据终端选择，不支持真彩色时使用16色
+    let fallback = || if support_true_color() { Theme::dark() } else { Theme::basic() };
+    if let Some(config) = themes.get(name) {
+        let base = config.base.as_deref().unwrap_or("dark");
+        let mut theme = Theme::builtin(base).unwrap_or_else(|| {
+            warnings.push(tf(Msg::ThemeBaseNotFound, &[&name, &base]));
+            fallback()
+        });
+        for (key, value) in &config.colors {
+            let color = match Color::from_str(value) {
+                Ok(color) => color,
+                Err(_) => {
+                    warnings.push(tf(Msg::ThemeInvalidColor, &[&name, &format!("{key}={value}")]));
+                    continue;
+                }
+            };
+            let field = match key.as_str() {
+                "buffer_bg" => &mut theme.buffer_bg,
+                "header_bg" => &mut theme.header_bg,
+                "header_fg" => &mut theme.header_fg,
+                "row_fg" => &mut theme.row_fg,
+                "selected_fg" => &mut theme.selected_fg,
+                "normal_row_bg" => &mut theme.normal_row_bg,
+                "alt_row_bg" => &mut theme.alt_row_bg,
+                "checked_fg" => &mut theme.checked_fg,
+                "marked_bg" => &mut theme.marked_bg,
+                "status_bg" => &mut theme.status_bg,
+                "status_fg" => &mut theme.status_fg,
+                "menu_key_fg" => &mut theme.menu_key_fg,
+                "menu_fg" => &mut theme.menu_fg,
+                "filter_edit_fg" => &mut theme.filter_edit_fg,
+                "match_fg" => &mut theme.match_fg,
+                "match_bg" => &mut theme.match_bg,
+                _ => {
+                    warnings.push(tf(Msg::UnknownConfig, &[&format!("themes.{name}.{key}")]));
+                    continue;
+                }
+            };
+            *field = color;
+        }
+        return theme;
+    }
+    if name == "auto" {
+        return fallback();
+    }
+    match Theme::builtin(name) {
+        // 明确选择的主题不替换，终端不支持真彩色时只给出警告
+        Some(theme) => {
+            if name != "basic" && !support_true_color() {
+                warnings.push(tf(Msg::ThemeNoTrueColor, &[&name]));
+            }
+            theme
+        }
+        None => {
+            warnings.push(tf(Msg::ThemeNotFound, &[&name]));
+            fallback()
+        }
+    }
+}